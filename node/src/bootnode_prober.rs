@@ -0,0 +1,235 @@
+//! Dials the bootnodes the IBP offchain worker publishes and stores the results for it.
+//!
+//! The runtime can't speak libp2p, so the offchain worker of a monitor node publishes the
+//! bootnodes to probe under `BOOTNODE_TARGETS_KEY` in persistent offchain storage. This task
+//! dials each of them, checks that the remote authenticates with the peer id of its multiaddr,
+//! measures the time until the connection is established and stores a `BootNodeProbeResult`,
+//! which the offchain worker submits as a health check.
+
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use ibp_runtime::pallet_ibp::{
+	bootnode_result_key, BootNodeProbeResult, BootNodeTarget, FailureCode, HealthCheckResult,
+	BOOTNODE_TARGETS_KEY,
+};
+use libp2p::{
+	core::{muxing::StreamMuxerBox, transport::Boxed, upgrade},
+	dns, identity,
+	multiaddr::Protocol,
+	noise, ping,
+	swarm::{dial_opts::DialOpts, DialError, SwarmBuilder, SwarmEvent},
+	tcp, websocket, yamux, Multiaddr, PeerId, Transport,
+};
+use sp_core::offchain::OffchainStorage;
+use std::{
+	str::FromStr,
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const LOG_TARGET: &str = "ibp-bootnode-prober";
+
+/// How often the published bootnodes are dialed.
+const PROBE_PERIOD: Duration = Duration::from_secs(60);
+
+/// How long a dial, including the handshake, may take.
+const DIAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Why dialing a bootnode failed.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ProbeError {
+	/// The multiaddr doesn't parse or doesn't end in `/p2p/<peer_id>`.
+	InvalidMultiaddr,
+	/// The remote authenticated with another peer id.
+	WrongPeerId,
+	/// The connection or the handshake failed.
+	Connection,
+	/// No connection was established within the timeout.
+	Timeout,
+}
+
+impl From<ProbeError> for FailureCode {
+	fn from(error: ProbeError) -> Self {
+		match error {
+			ProbeError::InvalidMultiaddr => FailureCode::InvalidResponse,
+			ProbeError::WrongPeerId => FailureCode::WrongPeerId,
+			ProbeError::Connection => FailureCode::Connection,
+			ProbeError::Timeout => FailureCode::Timeout,
+		}
+	}
+}
+
+/// Dial the bootnodes published in `storage` every [`PROBE_PERIOD`], forever.
+pub async fn run<S: OffchainStorage>(mut storage: S) {
+	loop {
+		let targets = storage
+			.get(sp_offchain::STORAGE_PREFIX, BOOTNODE_TARGETS_KEY)
+			.and_then(|targets| Vec::<BootNodeTarget>::decode(&mut &targets[..]).ok())
+			.unwrap_or_default();
+		for target in targets {
+			let result = probe_target(&target).await;
+			log::debug!(
+				target: LOG_TARGET,
+				"Probed bootnode of member service {}: {:?}",
+				target.member_service_id,
+				result,
+			);
+			storage.set(
+				sp_offchain::STORAGE_PREFIX,
+				&bootnode_result_key(target.member_service_id),
+				&result.encode(),
+			);
+		}
+		tokio::time::sleep(PROBE_PERIOD).await;
+	}
+}
+
+async fn probe_target(target: &BootNodeTarget) -> BootNodeProbeResult {
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|since_epoch| since_epoch.as_millis() as u64)
+		.unwrap_or_default();
+	let dialed = std::str::from_utf8(&target.multiaddr)
+		.map_err(|_| ProbeError::InvalidMultiaddr)
+		.and_then(|multiaddr| {
+			Multiaddr::from_str(multiaddr).map_err(|_| ProbeError::InvalidMultiaddr)
+		});
+	let dialed = match dialed {
+		Ok(multiaddr) => probe(multiaddr, DIAL_TIMEOUT).await,
+		Err(error) => Err(error),
+	};
+	match dialed {
+		Ok(handshake_time) => BootNodeProbeResult {
+			timestamp,
+			result: HealthCheckResult::Ok,
+			response_time_ms: handshake_time.as_millis().try_into().unwrap_or(u32::MAX),
+		},
+		Err(error) => BootNodeProbeResult {
+			timestamp,
+			result: HealthCheckResult::Failed(error.into()),
+			response_time_ms: 0,
+		},
+	}
+}
+
+/// Dial the bootnode at `multiaddr`, which must end in `/p2p/<peer_id>`, and return the time until
+/// a connection authenticated as that peer id was established.
+pub async fn probe(mut multiaddr: Multiaddr, timeout: Duration) -> Result<Duration, ProbeError> {
+	let expected = match multiaddr.pop() {
+		Some(Protocol::P2p(peer_id)) =>
+			PeerId::from_multihash(peer_id).map_err(|_| ProbeError::InvalidMultiaddr)?,
+		_ => return Err(ProbeError::InvalidMultiaddr),
+	};
+	let keypair = identity::Keypair::generate_ed25519();
+	let local_peer_id = keypair.public().to_peer_id();
+	let transport = build_transport(&keypair, timeout).map_err(|_| ProbeError::Connection)?;
+	let mut swarm = SwarmBuilder::with_tokio_executor(
+		transport,
+		ping::Behaviour::new(ping::Config::new()),
+		local_peer_id,
+	)
+	.build();
+	let started = Instant::now();
+	swarm
+		.dial(DialOpts::peer_id(expected).addresses(vec![multiaddr]).build())
+		.map_err(|_| ProbeError::Connection)?;
+	let established = async {
+		loop {
+			match swarm.select_next_some().await {
+				SwarmEvent::ConnectionEstablished { peer_id, .. } if peer_id == expected =>
+					return Ok(started.elapsed()),
+				SwarmEvent::ConnectionEstablished { .. } => return Err(ProbeError::WrongPeerId),
+				SwarmEvent::OutgoingConnectionError {
+					error: DialError::WrongPeerId { .. },
+					..
+				} => return Err(ProbeError::WrongPeerId),
+				SwarmEvent::OutgoingConnectionError { .. } => return Err(ProbeError::Connection),
+				_ => {},
+			}
+		}
+	};
+	tokio::time::timeout(timeout, established)
+		.await
+		.map_err(|_| ProbeError::Timeout)?
+}
+
+/// TCP and WebSocket transport, with DNS resolution, authenticated with Noise and multiplexed with
+/// Yamux like Substrate nodes.
+fn build_transport(
+	keypair: &identity::Keypair,
+	timeout: Duration,
+) -> std::io::Result<Boxed<(PeerId, StreamMuxerBox)>> {
+	let tcp = || tcp::tokio::Transport::new(tcp::Config::new().nodelay(true));
+	let websocket = websocket::WsConfig::new(dns::TokioDnsConfig::system(tcp())?);
+	let transport = dns::TokioDnsConfig::system(tcp())?.or_transport(websocket);
+	let noise = noise::Config::new(keypair)
+		.map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
+	Ok(transport
+		.upgrade(upgrade::Version::V1Lazy)
+		.authenticate(noise)
+		.multiplex(yamux::Config::default())
+		.timeout(timeout)
+		.boxed())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Start an in-process libp2p peer and return its peer id and listen address.
+	async fn spawn_peer() -> (PeerId, Multiaddr) {
+		let keypair = identity::Keypair::generate_ed25519();
+		let peer_id = keypair.public().to_peer_id();
+		let transport = build_transport(&keypair, DIAL_TIMEOUT).unwrap();
+		let mut swarm = SwarmBuilder::with_tokio_executor(
+			transport,
+			ping::Behaviour::new(ping::Config::new()),
+			peer_id,
+		)
+		.build();
+		swarm.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+		let address = loop {
+			if let SwarmEvent::NewListenAddr { address, .. } = swarm.select_next_some().await {
+				break address
+			}
+		};
+		tokio::spawn(async move {
+			loop {
+				swarm.select_next_some().await;
+			}
+		});
+		(peer_id, address)
+	}
+
+	#[tokio::test]
+	async fn probe_measures_handshake_with_expected_peer() {
+		let (peer_id, address) = spawn_peer().await;
+		let multiaddr = address.with(Protocol::P2p(peer_id.into()));
+		assert!(probe(multiaddr, DIAL_TIMEOUT).await.is_ok());
+	}
+
+	#[tokio::test]
+	async fn probe_rejects_wrong_peer_id() {
+		let (_, address) = spawn_peer().await;
+		let other = identity::Keypair::generate_ed25519().public().to_peer_id();
+		let multiaddr = address.with(Protocol::P2p(other.into()));
+		assert_eq!(probe(multiaddr, DIAL_TIMEOUT).await, Err(ProbeError::WrongPeerId));
+	}
+
+	#[tokio::test]
+	async fn probe_requires_peer_id() {
+		let (_, address) = spawn_peer().await;
+		assert_eq!(probe(address, DIAL_TIMEOUT).await, Err(ProbeError::InvalidMultiaddr));
+	}
+
+	#[tokio::test]
+	async fn probe_target_stores_failure_code() {
+		let target = BootNodeTarget {
+			member_service_id: 7,
+			multiaddr:
+				b"/ip4/127.0.0.1/tcp/1/p2p/12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp"
+					.to_vec(),
+		};
+		let result = probe_target(&target).await;
+		assert_eq!(result.result, HealthCheckResult::Failed(FailureCode::Connection));
+	}
+}
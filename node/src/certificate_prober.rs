@@ -0,0 +1,145 @@
+//! Reads the TLS certificates of the RPC endpoints the IBP offchain worker publishes and stores
+//! them for it.
+//!
+//! The offchain HTTP API doesn't expose certificates, so the offchain worker of a monitor node
+//! publishes the endpoints under `CERTIFICATE_TARGETS_KEY` in persistent offchain storage. This
+//! task connects to each of them, reads the leaf certificate without validating it, as expired and
+//! unexpected certificates must be reported too, and stores a `CertificateReport` that the offchain
+//! worker attaches to the next health check.
+
+use codec::{Decode, Encode};
+use ibp_runtime::pallet_ibp::{
+	certificate_report_key, CertificateReport, CertificateTarget, CERTIFICATE_TARGETS_KEY,
+};
+use sp_core::offchain::OffchainStorage;
+use sp_runtime::BoundedVec;
+use std::{
+	sync::Arc,
+	time::{Duration, SystemTime},
+};
+use tokio::net::TcpStream;
+use tokio_rustls::{
+	rustls::{
+		client::{ServerCertVerified, ServerCertVerifier},
+		Certificate, ClientConfig, Error as TlsError, ServerName,
+	},
+	TlsConnector,
+};
+
+const LOG_TARGET: &str = "ibp-certificate-prober";
+
+/// How often the certificates of the published endpoints are read.
+const PROBE_PERIOD: Duration = Duration::from_secs(600);
+
+/// How long connecting, including the TLS handshake, may take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Accepts any certificate, so that the certificate can be reported whatever its state.
+struct AcceptAnyCertificate;
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+	fn verify_server_cert(
+		&self,
+		_end_entity: &Certificate,
+		_intermediates: &[Certificate],
+		_server_name: &ServerName,
+		_scts: &mut dyn Iterator<Item = &[u8]>,
+		_ocsp_response: &[u8],
+		_now: SystemTime,
+	) -> Result<ServerCertVerified, TlsError> {
+		Ok(ServerCertVerified::assertion())
+	}
+}
+
+/// Read the certificates of the endpoints published in `storage` every [`PROBE_PERIOD`], forever.
+pub async fn run<S: OffchainStorage>(mut storage: S) {
+	let config = ClientConfig::builder()
+		.with_safe_defaults()
+		.with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate))
+		.with_no_client_auth();
+	let connector = TlsConnector::from(Arc::new(config));
+	loop {
+		let targets = storage
+			.get(sp_offchain::STORAGE_PREFIX, CERTIFICATE_TARGETS_KEY)
+			.and_then(|targets| Vec::<CertificateTarget>::decode(&mut &targets[..]).ok())
+			.unwrap_or_default();
+		for target in targets {
+			match read_certificate(&connector, &target).await {
+				Ok(report) => storage.set(
+					sp_offchain::STORAGE_PREFIX,
+					&certificate_report_key(target.member_service_id),
+					&report.encode(),
+				),
+				Err(error) => log::debug!(
+					target: LOG_TARGET,
+					"Failed to read the certificate of member service {}: {}",
+					target.member_service_id,
+					error,
+				),
+			}
+		}
+		tokio::time::sleep(PROBE_PERIOD).await;
+	}
+}
+
+/// Connect to `target` and report the leaf certificate it presents.
+async fn read_certificate(
+	connector: &TlsConnector,
+	target: &CertificateTarget,
+) -> Result<CertificateReport, String> {
+	let host = std::str::from_utf8(&target.host).map_err(|error| error.to_string())?;
+	let server_name = ServerName::try_from(host).map_err(|error| error.to_string())?;
+	let connect = async {
+		let stream = TcpStream::connect((host, target.port)).await?;
+		connector.connect(server_name, stream).await
+	};
+	let stream = tokio::time::timeout(CONNECT_TIMEOUT, connect)
+		.await
+		.map_err(|_| "timed out".to_string())?
+		.map_err(|error| error.to_string())?;
+	let certificate = stream
+		.get_ref()
+		.1
+		.peer_certificates()
+		.and_then(|certificates| certificates.first())
+		.ok_or_else(|| "no certificate presented".to_string())?;
+	certificate_report(&certificate.0)
+}
+
+/// Report of a DER encoded certificate.
+fn certificate_report(der: &[u8]) -> Result<CertificateReport, String> {
+	let (_, certificate) =
+		x509_parser::parse_x509_certificate(der).map_err(|error| error.to_string())?;
+	let expires_at = certificate.validity().not_after.timestamp().max(0) as u64 * 1000;
+	let issuer = certificate.issuer().to_string().into_bytes();
+	Ok(CertificateReport {
+		fingerprint: sp_core::hashing::sha2_256(der),
+		issuer: BoundedVec::truncate_from(issuer),
+		expires_at,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::bytes::from_hex;
+
+	/// Self-signed certificate of `rpc.helikon.io`, valid until 2036-10-15T20:41:38Z.
+	const CERTIFICATE: &[u8] = include_bytes!("fixtures/rpc.helikon.io.der");
+
+	#[test]
+	fn certificate_report_reads_fingerprint_issuer_and_expiry() {
+		let report = certificate_report(CERTIFICATE).unwrap();
+		assert_eq!(
+			report.fingerprint.to_vec(),
+			from_hex("f4c6dc58c5a14df4681793da377d964a995e76a7c9bd3f3000c3f6db43e62627").unwrap(),
+		);
+		assert_eq!(report.issuer.into_inner(), b"CN=rpc.helikon.io, O=Helikon".to_vec());
+		assert_eq!(report.expires_at, 2_107_716_098_000);
+	}
+
+	#[test]
+	fn certificate_report_rejects_invalid_der() {
+		assert!(certificate_report(&CERTIFICATE[1..]).is_err());
+	}
+}
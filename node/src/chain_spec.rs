@@ -179,5 +179,7 @@ fn testnet_genesis(
 			key: Some(root_key),
 		},
 		transaction_payment: Default::default(),
+		ibp_council: Default::default(),
+		ibp_council_membership: Default::default(),
 	}
 }
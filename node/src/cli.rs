@@ -13,7 +13,7 @@ pub struct Cli {
 pub enum Subcommand {
 	/// Key management cli utilities
 	#[command(subcommand)]
-	Key(sc_cli::KeySubcommand),
+	Key(KeySubcommand),
 
 	/// Build a chain specification.
 	BuildSpec(sc_cli::BuildSpecCmd),
@@ -51,3 +51,22 @@ pub enum Subcommand {
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
 }
+
+/// Substrate key management subcommands, plus IBP monitor and contact keys.
+#[derive(Debug, clap::Subcommand)]
+pub enum KeySubcommand {
+	#[command(flatten)]
+	Substrate(sc_cli::KeySubcommand),
+
+	/// Insert an IBP monitor key into the keystore, generating one if no URI is given.
+	InsertMonitor(crate::monitor_key::InsertMonitorKeyCmd),
+
+	/// Insert an IBP contact key into the keystore, generating one if no secret is given.
+	InsertContact(crate::contact_key::InsertContactKeyCmd),
+
+	/// Seal a member's contact details to the IBP contact key.
+	EncryptContact(crate::contact_key::EncryptContactCmd),
+
+	/// Decrypt a member's contact details with the contact keys in the keystore.
+	DecryptContact(crate::contact_key::DecryptContactCmd),
+}
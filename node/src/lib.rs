@@ -1,3 +1,5 @@
+pub mod bootnode_prober;
+pub mod certificate_prober;
 pub mod chain_spec;
 pub mod rpc;
 pub mod service;
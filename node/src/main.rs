@@ -1,13 +1,18 @@
 //! Substrate Node Template CLI library.
 #![warn(missing_docs)]
 
+mod bootnode_prober;
+mod certificate_prober;
 mod chain_spec;
 #[macro_use]
 mod service;
 mod benchmarking;
 mod cli;
 mod command;
+mod contact_key;
+mod monitor_key;
 mod rpc;
+mod websocket_prober;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
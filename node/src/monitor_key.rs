@@ -0,0 +1,93 @@
+//! Implementation of the `key insert-monitor` subcommand.
+
+use ibp_runtime::pallet_ibp;
+use sc_cli::{CliConfiguration, Error, KeystoreParams, Result, SharedParams};
+use sc_keystore::LocalKeystore;
+use sc_service::config::KeystoreConfig;
+use sp_core::{crypto::Ss58Codec, hexdisplay::HexDisplay, sr25519, Pair};
+use sp_keystore::Keystore;
+
+/// Insert an IBP monitor key (`ibpm`, sr25519) into the keystore.
+///
+/// A new key is generated when `--suri` is omitted. Bind the printed public key to a monitor with
+/// `IBP::set_monitor_key` so the node can sign health checks without holding funds.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct InsertMonitorKeyCmd {
+	/// The secret key URI. A new key is generated, and its secret phrase printed, if omitted.
+	#[arg(long)]
+	suri: Option<String>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl InsertMonitorKeyCmd {
+	/// Run the command against the keystore of the node configuration.
+	pub fn run(&self, keystore: KeystoreConfig) -> Result<()> {
+		let (path, password) = match keystore {
+			KeystoreConfig::Path { path, password } => (path, password),
+			KeystoreConfig::InMemory =>
+				return Err(Error::Input("Monitor keys need an on-disk keystore".into())),
+		};
+		let suri = match &self.suri {
+			Some(suri) => suri.clone(),
+			None => {
+				let (_, phrase, _) = sr25519::Pair::generate_with_phrase(None);
+				println!("Secret phrase:      {}", phrase);
+				phrase
+			},
+		};
+		let public = sr25519::Pair::from_string(&suri, None)
+			.map_err(|_| Error::Input("Invalid secret key URI".into()))?
+			.public();
+		let keystore = LocalKeystore::open(path, password)?;
+		Keystore::insert(&keystore, pallet_ibp::KEY_TYPE, &suri, public.as_ref())
+			.map_err(|_| Error::KeystoreOperation)?;
+		println!("Public key (hex):   0x{}", HexDisplay::from(&public.0));
+		println!("Public key (SS58):  {}", public.to_ss58check());
+		Ok(())
+	}
+}
+
+impl CliConfiguration for InsertMonitorKeyCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::Parser;
+
+	#[test]
+	fn monitor_key_is_inserted_into_the_keystore() {
+		let dir = tempfile::tempdir().unwrap();
+		let suri = "//Monitor";
+		InsertMonitorKeyCmd::parse_from(["insert-monitor", "--suri", suri])
+			.run(KeystoreConfig::Path { path: dir.path().to_path_buf(), password: None })
+			.unwrap();
+
+		let public = sr25519::Pair::from_string(suri, None).unwrap().public();
+		let keystore = LocalKeystore::open(dir.path(), None).unwrap();
+		assert_eq!(Keystore::sr25519_public_keys(&keystore, pallet_ibp::KEY_TYPE), vec![public]);
+		assert!(Keystore::sr25519_sign(&keystore, pallet_ibp::KEY_TYPE, &public, b"health check")
+			.unwrap()
+			.is_some());
+	}
+
+	#[test]
+	fn monitor_key_needs_an_on_disk_keystore() {
+		let cmd = InsertMonitorKeyCmd::parse_from(["insert-monitor", "--suri", "//Monitor"]);
+		assert!(cmd.run(KeystoreConfig::InMemory).is_err());
+	}
+}
@@ -1,7 +1,7 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 use ibp_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::BlockBackend;
+use sc_client_api::{Backend, BlockBackend};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 use sc_consensus_grandpa::SharedVoterState;
 pub use sc_executor::NativeElseWasmExecutor;
@@ -183,6 +183,24 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 			client.clone(),
 			network.clone(),
 		);
+		// Probe what the IBP offchain worker can't, on monitor nodes.
+		if let Some(offchain_storage) = backend.offchain_storage() {
+			task_manager.spawn_handle().spawn(
+				"ibp-bootnode-prober",
+				None,
+				crate::bootnode_prober::run(offchain_storage.clone()),
+			);
+			task_manager.spawn_handle().spawn(
+				"ibp-websocket-prober",
+				None,
+				crate::websocket_prober::run(offchain_storage.clone()),
+			);
+			task_manager.spawn_handle().spawn(
+				"ibp-certificate-prober",
+				None,
+				crate::certificate_prober::run(offchain_storage),
+			);
+		}
 	}
 
 	let role = config.role.clone();
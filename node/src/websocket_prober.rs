@@ -0,0 +1,272 @@
+//! Probes the WebSocket endpoints the IBP offchain worker publishes and stores the results for it.
+//!
+//! The offchain HTTP API can't open WebSockets, so the offchain worker of a monitor node publishes
+//! the endpoints probed over WSS under `WEBSOCKET_TARGETS_KEY` in persistent offchain storage.
+//! This task connects to each of them and, for RPC endpoints, queries `system_health`, the
+//! required methods, `System.Number` at the archive block of archive endpoints and the genesis hash
+//! over the connection. It stores a `WebSocketProbeResult`, which the offchain worker submits as a
+//! health check.
+
+use codec::{Decode, Encode};
+use futures::{SinkExt, StreamExt};
+use ibp_runtime::pallet_ibp::{
+	system_number_key, websocket_result_key, FailureCode, HealthCheckResult, WebSocketProbeResult,
+	WebSocketTarget, WEBSOCKET_TARGETS_KEY,
+};
+use serde_json::{json, Value};
+use sp_core::{
+	bytes::{from_hex, to_hex},
+	offchain::OffchainStorage,
+	H256,
+};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+const LOG_TARGET: &str = "ibp-websocket-prober";
+
+/// How often the published endpoints are probed.
+const PROBE_PERIOD: Duration = Duration::from_secs(60);
+
+/// How long a probe, including the handshake, may take.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Probe the endpoints published in `storage` every [`PROBE_PERIOD`], forever.
+pub async fn run<S: OffchainStorage>(mut storage: S) {
+	loop {
+		let targets = storage
+			.get(sp_offchain::STORAGE_PREFIX, WEBSOCKET_TARGETS_KEY)
+			.and_then(|targets| Vec::<WebSocketTarget>::decode(&mut &targets[..]).ok())
+			.unwrap_or_default();
+		for target in targets {
+			let result = probe_target(&target, PROBE_TIMEOUT).await;
+			log::debug!(
+				target: LOG_TARGET,
+				"Probed WebSocket endpoint of member service {}: {:?}",
+				target.member_service_id,
+				result,
+			);
+			storage.set(
+				sp_offchain::STORAGE_PREFIX,
+				&websocket_result_key(target.member_service_id),
+				&result.encode(),
+			);
+		}
+		tokio::time::sleep(PROBE_PERIOD).await;
+	}
+}
+
+/// Probe `target`, failing with `Timeout` if it takes longer than `timeout`.
+pub async fn probe_target(target: &WebSocketTarget, timeout: Duration) -> WebSocketProbeResult {
+	let timestamp = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|since_epoch| since_epoch.as_millis() as u64)
+		.unwrap_or_default();
+	let failed = |code| WebSocketProbeResult {
+		timestamp,
+		result: HealthCheckResult::Failed(code),
+		response_time_ms: 0,
+		peer_count: None,
+		is_syncing: None,
+		genesis_hash: None,
+	};
+	match tokio::time::timeout(timeout, probe(target, timestamp)).await {
+		Ok(Ok(result)) => result,
+		Ok(Err(code)) => failed(code),
+		Err(_) => failed(FailureCode::Timeout),
+	}
+}
+
+async fn probe(
+	target: &WebSocketTarget,
+	timestamp: u64,
+) -> Result<WebSocketProbeResult, FailureCode> {
+	let url = std::str::from_utf8(&target.url).map_err(|_| FailureCode::InvalidResponse)?;
+	let started = Instant::now();
+	let (mut socket, _) = connect_async(url).await.map_err(|_| FailureCode::Connection)?;
+	let mut result = WebSocketProbeResult {
+		timestamp,
+		result: HealthCheckResult::Ok,
+		response_time_ms: 0,
+		peer_count: None,
+		is_syncing: None,
+		genesis_hash: None,
+	};
+	if target.rpc {
+		let health = request(&mut socket, "system_health", json!([])).await?;
+		result.peer_count = health.get("peers").and_then(Value::as_u64).map(|peers| peers as u32);
+		result.is_syncing = health.get("isSyncing").and_then(Value::as_bool);
+	}
+	result.response_time_ms = started.elapsed().as_millis().try_into().unwrap_or(u32::MAX);
+	if !target.required_methods.is_empty() {
+		let methods = request(&mut socket, "rpc_methods", json!([])).await?;
+		let methods = methods
+			.get("methods")
+			.and_then(Value::as_array)
+			.ok_or(FailureCode::InvalidResponse)?;
+		let missing = target.required_methods.iter().any(|required| {
+			!methods
+				.iter()
+				.any(|method| method.as_str().map(str::as_bytes) == Some(&required[..]))
+		});
+		if missing {
+			return Err(FailureCode::MissingRpcMethod)
+		}
+	}
+	if let Some(block_hash) = target.archive_block_hash {
+		// Pruned nodes answer with `null` or an error.
+		let params =
+			json!([to_hex(&system_number_key(), false), to_hex(block_hash.as_bytes(), false)]);
+		match request(&mut socket, "state_getStorage", params).await {
+			Ok(Value::String(_)) => {},
+			Ok(Value::Null) | Err(FailureCode::JsonRpcError(_)) =>
+				return Err(FailureCode::StateUnavailable),
+			Ok(_) => return Err(FailureCode::InvalidResponse),
+			Err(code) => return Err(code),
+		}
+	}
+	if target.rpc {
+		// Queried on a best-effort basis; the pallet fails checks without it.
+		result.genesis_hash = request(&mut socket, "chain_getBlockHash", json!([0]))
+			.await
+			.ok()
+			.and_then(|hash| from_hex(hash.as_str()?).ok())
+			.filter(|hash| hash.len() == 32)
+			.map(|hash| H256::from_slice(&hash));
+	}
+	let _ = socket.close(None).await;
+	Ok(result)
+}
+
+/// Send a JSON-RPC request and return the `result` of the response.
+async fn request(socket: &mut Socket, method: &str, params: Value) -> Result<Value, FailureCode> {
+	let request = json!({ "id": 1, "jsonrpc": "2.0", "method": method, "params": params });
+	socket
+		.send(Message::Text(request.to_string()))
+		.await
+		.map_err(|_| FailureCode::Connection)?;
+	loop {
+		let message = match socket.next().await {
+			Some(Ok(message)) => message,
+			Some(Err(_)) | None => return Err(FailureCode::Connection),
+		};
+		let text = match message {
+			Message::Text(text) => text,
+			Message::Close(_) => return Err(FailureCode::Connection),
+			_ => continue,
+		};
+		let mut response: Value =
+			serde_json::from_str(&text).map_err(|_| FailureCode::InvalidResponse)?;
+		if let Some(error) = response.get("error") {
+			let code = error.get("code").and_then(Value::as_i64).unwrap_or_default();
+			return Err(FailureCode::JsonRpcError(code as i32))
+		}
+		return response.get_mut("result").map(Value::take).ok_or(FailureCode::InvalidResponse)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tokio::net::TcpListener;
+
+	const GENESIS: &str = "0x91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3";
+
+	const ARCHIVE_BLOCK: &str =
+		"0xc0096358534ec8d21d01d34b836eed476a1c343f8724fa2153dc0725ad797a90";
+
+	/// `twox128("System") ++ twox128("Number")`.
+	const SYSTEM_NUMBER_KEY: &str =
+		"0x26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac";
+
+	/// Serve `system_health`, `rpc_methods`, `chain_getBlockHash` and, like an archive node that
+	/// has only kept the state of [`ARCHIVE_BLOCK`], `state_getStorage` over WebSocket on a local
+	/// port and return its `ws://` URL.
+	async fn spawn_rpc_server() -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let address = listener.local_addr().unwrap();
+		tokio::spawn(async move {
+			while let Ok((stream, _)) = listener.accept().await {
+				tokio::spawn(async move {
+					let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+					while let Some(Ok(Message::Text(text))) = socket.next().await {
+						let request: Value = serde_json::from_str(&text).unwrap();
+						let result = match request["method"].as_str().unwrap() {
+							"system_health" => json!({ "peers": 12, "isSyncing": false }),
+							"rpc_methods" => json!({ "methods": ["system_health"] }),
+							"chain_getBlockHash" => json!(GENESIS),
+							"state_getStorage"
+								if request["params"] ==
+									json!([SYSTEM_NUMBER_KEY, ARCHIVE_BLOCK]) =>
+								json!("0x01000000"),
+							_ => Value::Null,
+						};
+						let response = json!({ "id": 1, "jsonrpc": "2.0", "result": result });
+						socket.send(Message::Text(response.to_string())).await.unwrap();
+					}
+				});
+			}
+		});
+		format!("ws://{}", address)
+	}
+
+	fn target(url: String, required_methods: Vec<Vec<u8>>) -> WebSocketTarget {
+		WebSocketTarget {
+			member_service_id: 0,
+			url: url.into_bytes(),
+			rpc: true,
+			required_methods,
+			archive_block_hash: None,
+		}
+	}
+
+	fn archive_target(url: String, block_hash: &str) -> WebSocketTarget {
+		let block_hash = H256::from_slice(&from_hex(block_hash).unwrap());
+		WebSocketTarget { archive_block_hash: Some(block_hash), ..target(url, vec![]) }
+	}
+
+	#[tokio::test]
+	async fn probe_queries_health_and_genesis_hash() {
+		let url = spawn_rpc_server().await;
+		let result =
+			probe_target(&target(url, vec![b"system_health".to_vec()]), PROBE_TIMEOUT).await;
+		assert_eq!(result.result, HealthCheckResult::Ok);
+		assert_eq!(result.peer_count, Some(12));
+		assert_eq!(result.is_syncing, Some(false));
+		assert_eq!(result.genesis_hash, Some(H256::from_slice(&from_hex(GENESIS).unwrap())));
+	}
+
+	#[tokio::test]
+	async fn probe_requires_methods() {
+		let url = spawn_rpc_server().await;
+		let result =
+			probe_target(&target(url, vec![b"state_traceBlock".to_vec()]), PROBE_TIMEOUT).await;
+		assert_eq!(result.result, HealthCheckResult::Failed(FailureCode::MissingRpcMethod));
+	}
+
+	#[tokio::test]
+	async fn archive_probe_queries_system_number_at_the_archive_block() {
+		let url = spawn_rpc_server().await;
+		let result = probe_target(&archive_target(url, ARCHIVE_BLOCK), PROBE_TIMEOUT).await;
+		assert_eq!(result.result, HealthCheckResult::Ok);
+		assert_eq!(result.genesis_hash, Some(H256::from_slice(&from_hex(GENESIS).unwrap())));
+	}
+
+	#[tokio::test]
+	async fn archive_probe_reports_pruned_state() {
+		let url = spawn_rpc_server().await;
+		let result = probe_target(&archive_target(url, GENESIS), PROBE_TIMEOUT).await;
+		assert_eq!(result.result, HealthCheckResult::Failed(FailureCode::StateUnavailable));
+	}
+
+	#[tokio::test]
+	async fn probe_reports_unreachable_endpoint() {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("ws://{}", listener.local_addr().unwrap());
+		drop(listener);
+		let result = probe_target(&target(url, vec![]), PROBE_TIMEOUT).await;
+		assert_eq!(result.result, HealthCheckResult::Failed(FailureCode::Connection));
+	}
+}
@@ -0,0 +1,26 @@
+//! Runtime API of pallet-ibp.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_ibp::{CertificateStatus, MaintenanceWindow, MemberDetails, VerifiedIdentity};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Queries of the IBP pallet's state.
+	#[api_version(3)]
+	pub trait IbpApi<BlockNumber>
+	where
+		BlockNumber: Codec,
+	{
+		/// Latest TLS certificate reported for a member service.
+		fn certificate_status(member_service_id: u32) -> Option<CertificateStatus<BlockNumber>>;
+		/// Current and upcoming maintenance windows of a member service.
+		#[api_version(2)]
+		fn maintenance_windows(member_service_id: u32) -> Vec<MaintenanceWindow<BlockNumber>>;
+		/// Registry data of a member, with its display name, web and email if a registrar
+		/// verified them.
+		#[api_version(3)]
+		fn member(member_id: u32) -> Option<MemberDetails>;
+	}
+}
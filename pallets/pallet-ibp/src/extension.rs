@@ -0,0 +1,106 @@
+use crate::{Call, Config, Error, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::DispatchInfo,
+	sp_runtime::{
+		traits::{DispatchInfoOf, Dispatchable, SaturatedConversion, SignedExtension},
+		transaction_validity::{
+			InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+		},
+	},
+	sp_std::{fmt, marker::PhantomData, prelude::*},
+	traits::{Get, IsSubType},
+};
+use scale_info::TypeInfo;
+
+/// Custom `InvalidTransaction` code for health checks submitted within `HealthCheckInterval`.
+pub const HEALTH_CHECK_TOO_FREQUENT: u8 = 1;
+
+/// Maps a health check rejection to the reason reported by transaction validation.
+pub(crate) fn health_check_invalidity<T: Config>(error: Error<T>) -> InvalidTransaction {
+	match error {
+		Error::<T>::MonitorNotFound => InvalidTransaction::BadSigner,
+		Error::<T>::HealthCheckTooFrequent => InvalidTransaction::Custom(HEALTH_CHECK_TOO_FREQUENT),
+		_ => InvalidTransaction::Call,
+	}
+}
+
+/// Validates `submit_health_check` calls before they enter the transaction pool.
+///
+/// Health checks from registered monitors don't pay fees, so everything that would make the call
+/// fail at dispatch has to be rejected here instead: unknown monitors, unknown member services
+/// and submissions made before `HealthCheckInterval` has passed since the monitor's last check of
+/// the same member service. All other calls pass through untouched.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckHealthCheck<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckHealthCheck<T> {
+	/// Create new `SignedExtension` to check health check submissions.
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckHealthCheck<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for CheckHealthCheck<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckHealthCheck")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckHealthCheck<T>
+where
+	T::RuntimeCall: Dispatchable<Info = DispatchInfo> + IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckHealthCheck";
+	type AccountId = T::AccountId;
+	type Call = T::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if let Some(Call::submit_health_check { health_check }) = call.is_sub_type() {
+			let member_service_id = health_check.member_service_id;
+			Pallet::<T>::validate_health_check(who, member_service_id)
+				.map_err(health_check_invalidity::<T>)?;
+			return Ok(ValidTransaction {
+				provides: vec![(b"ibp/health-check", who, member_service_id).encode()],
+				longevity: T::HealthCheckInterval::get().saturated_into::<u64>(),
+				..Default::default()
+			})
+		}
+		Ok(ValidTransaction::default())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
@@ -1,26 +1,188 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
 use codec::{Decode, Encode, MaxEncodedLen};
-use frame_support::RuntimeDebug;
+use frame_support::{
+	sp_std::{boxed::Box, collections::btree_map::BTreeMap, vec::Vec},
+	RuntimeDebug,
+};
 // Re-export pallet items so that they can be accessed from the crate namespace.
 use frame_support::{
+	dispatch::{GetDispatchInfo, PostDispatchInfo, UnfilteredDispatchable},
 	pallet_prelude::*,
 	sp_runtime::SaturatedConversion,
-	traits::{Currency, ReservableCurrency},
+	traits::{BalanceStatus, ChangeMembers, Currency, ExistenceRequirement, ReservableCurrency},
+	PalletId,
+};
+use frame_system::{
+	offchain::{AppCrypto, SendTransactionTypes, SignedPayload, SigningTypes},
+	pallet_prelude::*,
 };
-use frame_system::pallet_prelude::*;
 pub use pallet::*;
 use scale_info::TypeInfo;
+use sp_core::{crypto::KeyTypeId, H256};
+use sp_runtime::{
+	traits::{AccountIdConversion, Dispatchable, IdentifyAccount, One, Saturating, Zero},
+	BoundToRuntimeAppPublic, Perbill,
+};
 
 pub mod weights;
 pub use weights::*;
 
+mod extension;
+pub use extension::*;
+
+pub mod migrations;
+pub mod multiaddr;
+mod offchain;
+pub use offchain::{
+	bootnode_result_key, certificate_report_key, system_number_key, websocket_result_key,
+	BootNodeProbeResult, BootNodeTarget, CertificateTarget, WebSocketProbeResult, WebSocketTarget,
+	BOOTNODE_TARGETS_KEY, CERTIFICATE_TARGETS_KEY, DNS_OVER_HTTPS_URL_KEY, WEBSOCKET_TARGETS_KEY,
+};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+const MILLISECS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Member services whose heights are compared in one health check window of a service.
+const MAX_HEIGHT_REPORTS: u32 = 128;
+
+/// Key type of the keys monitors sign health checks with.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"ibpm");
+
+/// Key type of the x25519 keys the admin group decrypts member contacts with. Only the node's
+/// `key` subcommands use it.
+pub const CONTACT_KEY_TYPE: KeyTypeId = KeyTypeId(*b"ibpc");
+
+/// Monitor keys, stored in the node keystore under [`KEY_TYPE`].
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::{Public as Sr25519Public, Signature as Sr25519Signature};
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		MultiSignature, MultiSigner,
+	};
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct MonitorAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for MonitorAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = Sr25519Signature;
+		type GenericPublic = Sr25519Public;
+	}
+}
+
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
 pub enum ServiceType {
+	/// Full node RPC over both WSS and HTTPS.
 	RPC,
 	BootNode,
+	/// RPC that must serve historical state.
+	ArchiveRPC,
+	/// RPC over WSS only.
+	WssRPC,
+	/// RPC over HTTPS only.
+	HttpRPC,
+	/// Bootnode for light clients such as substrate-connect and smoldot, over WSS.
+	LightClientBootNode,
+	IpfsGateway,
+	Telemetry,
+}
+
+impl ServiceType {
+	/// Whether services of this type are probed with JSON-RPC requests.
+	pub fn is_rpc(&self) -> bool {
+		matches!(self, Self::RPC | Self::ArchiveRPC | Self::WssRPC | Self::HttpRPC)
+	}
+
+	/// Whether services of this type are libp2p bootnodes, probed by dialing them.
+	pub fn is_bootnode(&self) -> bool {
+		matches!(self, Self::BootNode | Self::LightClientBootNode)
+	}
+}
+
+/// Protocol a service is expected to be reachable over.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum Protocol {
+	Https,
+	Wss,
+	Libp2p,
+	/// libp2p over secure WebSockets, as light clients dial.
+	Libp2pWss,
+}
+
+/// How monitors probe a service.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct ProbeParams {
+	pub protocol: Protocol,
+	/// RPC methods the endpoint must expose in `rpc_methods`.
+	pub required_methods: BoundedVec<BoundedVec<u8, ConstU32<64>>, ConstU32<16>>,
+	/// Whether the endpoint must serve historical state.
+	pub archive: bool,
+}
+
+impl ProbeParams {
+	/// Probe parameters a service of type `ty` is registered with.
+	pub fn default_for(ty: &ServiceType) -> Self {
+		let protocol = match ty {
+			ServiceType::RPC |
+			ServiceType::ArchiveRPC |
+			ServiceType::HttpRPC |
+			ServiceType::IpfsGateway => Protocol::Https,
+			ServiceType::WssRPC | ServiceType::Telemetry => Protocol::Wss,
+			ServiceType::BootNode => Protocol::Libp2p,
+			ServiceType::LightClientBootNode => Protocol::Libp2pWss,
+		};
+		ProbeParams {
+			protocol,
+			required_methods: BoundedVec::default(),
+			archive: *ty == ServiceType::ArchiveRPC,
+		}
+	}
+
+	/// Whether monitors can probe a service of type `ty` with these parameters. Only RPC
+	/// endpoints list their methods and serve historical state.
+	pub fn supports(&self, ty: &ServiceType) -> bool {
+		let protocol = match ty {
+			ServiceType::RPC |
+			ServiceType::ArchiveRPC |
+			ServiceType::WssRPC |
+			ServiceType::HttpRPC => matches!(self.protocol, Protocol::Https | Protocol::Wss),
+			ServiceType::BootNode | ServiceType::LightClientBootNode =>
+				matches!(self.protocol, Protocol::Libp2p | Protocol::Libp2pWss),
+			ServiceType::IpfsGateway => self.protocol == Protocol::Https,
+			ServiceType::Telemetry => self.protocol == Protocol::Wss,
+		};
+		protocol &&
+			(ty.is_rpc() || self.required_methods.is_empty()) &&
+			(!self.archive || ty.is_rpc())
+	}
+}
+
+/// A chain that services are provided for.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Network {
+	id: u32,
+	name: BoundedVec<u8, ConstU32<64>>,
+	/// Hash of block 0, which monitors compare with what endpoints serve.
+	genesis_hash: H256,
+	/// Network of the relay chain, for parachains.
+	relay_network_id: Option<u32>,
+	/// Parachain id on the relay chain, for parachains.
+	para_id: Option<u32>,
+	ss58_prefix: u16,
+	token_symbol: BoundedVec<u8, ConstU32<8>>,
+	/// Hash of an old block whose state archive endpoints must still serve.
+	archive_block_hash: Option<H256>,
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
@@ -29,12 +191,97 @@ pub struct Service {
 	ty: ServiceType,
 	name: BoundedVec<u8, ConstU32<64>>,
 	url_path: BoundedVec<u8, ConstU32<32>>,
+	/// Network the service's endpoints must serve. `None` for services registered before
+	/// networks existed, until the admin sets it.
+	network_id: Option<u32>,
+	probe: ProbeParams,
+	/// Lowest level a member must hold to register member services of the service.
+	min_level: u8,
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
 pub struct Member {
 	id: u32,
 	name: BoundedVec<u8, ConstU32<64>>,
+	/// Determines the services the member may run, and weights its payouts.
+	level: u8,
+}
+
+/// Number of eras in a row a member met, or missed, the SLO with all its member services.
+#[derive(Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct SlaStreak {
+	pub met: u32,
+	pub missed: u32,
+}
+
+/// Pending application of an account for membership.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Application<Balance, BlockNumber> {
+	pub name: BoundedVec<u8, ConstU32<64>>,
+	pub metadata: BoundedVec<u8, ConstU32<256>>,
+	pub deposit: Balance,
+	/// Block at which the application is dropped if it wasn't decided on.
+	pub expires_at: BlockNumber,
+	/// Ids of the members that voted to approve the application.
+	pub ayes: BoundedVec<u32, ConstU32<32>>,
+	/// Ids of the members that voted to reject the application.
+	pub nays: BoundedVec<u32, ConstU32<32>>,
+}
+
+/// Public x25519 key of the admin group that members encrypt their contacts to.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct ContactKey {
+	/// Incremented each time the key is rotated.
+	pub id: u32,
+	pub public: [u8; 32],
+}
+
+/// On-call contact details of a member, sealed to a contact key.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct EncryptedContact {
+	/// Id of the contact key the details are sealed to.
+	pub key_id: u32,
+	/// Incremented on each update.
+	pub version: u32,
+	pub ciphertext: BoundedVec<u8, ConstU32<512>>,
+}
+
+/// Identity fields of an account that a registrar judged.
+#[derive(Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct VerifiedIdentity {
+	pub display: Vec<u8>,
+	pub web: Vec<u8>,
+	pub email: Vec<u8>,
+}
+
+/// Registry data of a member along with its verified identity, for the runtime API.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct MemberDetails {
+	pub id: u32,
+	pub name: Vec<u8>,
+	pub level: u8,
+	pub identity: Option<VerifiedIdentity>,
+}
+
+/// Source of verified identities of accounts, such as `pallet_identity`.
+pub trait VerifyIdentity<AccountId> {
+	/// Identity of `who`, if a registrar judged it at the required level.
+	fn verified_identity(who: &AccountId) -> Option<VerifiedIdentity>;
+}
+
+impl<AccountId> VerifyIdentity<AccountId> for () {
+	fn verified_identity(_who: &AccountId) -> Option<VerifiedIdentity> {
+		None
+	}
+}
+
+/// Full libp2p address of a bootnode, validated with [`multiaddr::bootnode_peer_id`].
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct BootNodeAddress {
+	/// Multiaddr ending in `/p2p/<peer_id>`.
+	pub multiaddr: BoundedVec<u8, ConstU32<256>>,
+	/// Base58 peer id the bootnode must authenticate as.
+	pub peer_id: BoundedVec<u8, ConstU32<64>>,
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
@@ -45,32 +292,525 @@ pub struct MemberService {
 	name: BoundedVec<u8, ConstU32<64>>,
 	address: BoundedVec<u8, ConstU32<128>>,
 	port: u16,
+	/// Set with `set_bootnode` for bootnode services.
+	bootnode: Option<BootNodeAddress>,
+}
+
+/// Whether monitors confirmed that a member service's member controls its address.
+#[derive(
+	Clone, Copy, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo,
+)]
+pub enum VerificationStatus {
+	/// Not confirmed yet. Health checks of the member service are rejected, so it earns nothing.
+	#[default]
+	Unverified,
+	Verified,
+}
+
+/// Token a member has to publish to prove control of a member service's address, either at
+/// `https://<address>/.well-known/ibp-challenge` or in a TXT record of
+/// `_ibp-challenge.<address>`, `0x`-prefixed and hex encoded.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct DomainChallenge<AccountId, BlockNumber> {
+	pub token: H256,
+	pub issued_at: BlockNumber,
+	/// Monitors that found the token published.
+	pub attestations: BoundedVec<AccountId, ConstU32<16>>,
+}
+
+/// Planned downtime of a member service, from block `start` up to but excluding block `end`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct MaintenanceWindow<BlockNumber> {
+	pub start: BlockNumber,
+	pub end: BlockNumber,
+	pub reason: BoundedVec<u8, ConstU32<128>>,
+}
+
+impl<BlockNumber: PartialOrd> MaintenanceWindow<BlockNumber> {
+	/// Whether `block_number` falls within the window.
+	pub fn contains(&self, block_number: &BlockNumber) -> bool {
+		self.start <= *block_number && *block_number < self.end
+	}
+}
+
+/// Why a health check failed.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum FailureCode {
+	/// The reason is not known. Used for checks recorded before failure codes existed.
+	Unknown,
+	/// The endpoint's domain name could not be resolved.
+	Dns,
+	/// No TCP connection could be established.
+	TcpConnect,
+	/// The TLS handshake failed.
+	Tls,
+	/// The connection failed in a way the prober can't attribute to DNS, TCP or TLS.
+	Connection,
+	/// No response arrived within the prober's timeout.
+	Timeout,
+	/// The endpoint answered with a non-success HTTP status.
+	HttpStatus(u16),
+	/// The response was not a well-formed JSON-RPC response.
+	InvalidResponse,
+	/// The endpoint answered with a JSON-RPC error code.
+	JsonRpcError(i32),
+	/// The node behind the endpoint is still syncing.
+	Syncing,
+	/// The node behind the endpoint serves a different chain.
+	WrongChain,
+	/// The endpoint doesn't expose an RPC method the service requires.
+	MissingRpcMethod,
+	/// The bootnode authenticated with a peer id other than the registered one.
+	WrongPeerId,
+	/// The endpoint doesn't serve the state of the network's archive block.
+	StateUnavailable,
+	/// The endpoint presented a certificate other than the expected one.
+	CertificateMismatch,
+	/// The endpoint presented an expired certificate.
+	CertificateExpired,
+}
+
+impl FailureCode {
+	/// How badly a failure affects users, for picking the peak failure of an incident. Serving
+	/// the wrong thing is worse than being unreachable, which is worse than answering badly.
+	pub fn severity(&self) -> u8 {
+		match self {
+			Self::Unknown => 0,
+			Self::Syncing => 1,
+			Self::HttpStatus(_) |
+			Self::InvalidResponse |
+			Self::JsonRpcError(_) |
+			Self::MissingRpcMethod |
+			Self::StateUnavailable |
+			Self::CertificateExpired => 2,
+			Self::Dns | Self::TcpConnect | Self::Tls | Self::Connection | Self::Timeout => 3,
+			Self::WrongChain | Self::WrongPeerId | Self::CertificateMismatch => 4,
+		}
+	}
+}
+
+/// Outcome of a health check.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum HealthCheckResult {
+	Ok,
+	Failed(FailureCode),
 }
 
 #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
 pub struct HealthCheck {
-	member_service_id: u32,
-	timestamp: u64,
-	status: bool,
-	response_time_ms: u32,
+	pub member_service_id: u32,
+	pub timestamp: u64,
+	pub result: HealthCheckResult,
+	pub response_time_ms: u32,
+	/// Best block reported by the endpoint.
+	pub best_block: Option<u32>,
+	/// Finalized block reported by the endpoint.
+	pub finalized_block: Option<u32>,
+	/// Number of peers reported by the endpoint.
+	pub peer_count: Option<u32>,
+	/// Whether the endpoint reported its node as syncing.
+	pub is_syncing: Option<bool>,
+	/// Genesis hash the endpoint reported, from `chain_getBlockHash(0)`.
+	pub genesis_hash: Option<H256>,
+	/// TLS certificate the endpoint presented.
+	pub certificate: Option<CertificateReport>,
+}
+
+impl HealthCheck {
+	/// A check result reported as `Ok` by a node that is still syncing is a `Syncing` failure. A
+	/// node serving a chain other than the one with `genesis_hash` is a `WrongChain` failure,
+	/// whatever else was reported, and an `Ok` result without a genesis hash to compare is an
+	/// `InvalidResponse` failure.
+	fn normalize(&mut self, genesis_hash: Option<H256>) {
+		if self.result == HealthCheckResult::Ok && self.is_syncing == Some(true) {
+			self.result = HealthCheckResult::Failed(FailureCode::Syncing);
+		}
+		match (genesis_hash, self.genesis_hash) {
+			(Some(expected), Some(reported)) if expected != reported =>
+				self.result = HealthCheckResult::Failed(FailureCode::WrongChain),
+			(Some(_), None) if self.result == HealthCheckResult::Ok =>
+				self.result = HealthCheckResult::Failed(FailureCode::InvalidResponse),
+			_ => (),
+		}
+	}
+
+	/// Whether the member service was found healthy.
+	pub fn is_up(&self) -> bool {
+		self.result == HealthCheckResult::Ok
+	}
+}
+
+/// TLS certificate an endpoint presented, as reported by a monitor.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct CertificateReport {
+	/// SHA-256 of the DER encoded leaf certificate.
+	pub fingerprint: [u8; 32],
+	/// Distinguished name of the issuer.
+	pub issuer: BoundedVec<u8, ConstU32<128>>,
+	/// Unix time the certificate expires at, in milliseconds.
+	pub expires_at: u64,
+}
+
+/// Certificate a member service is expected to present. Unset fields aren't checked.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct ExpectedCertificate {
+	pub fingerprint: Option<[u8; 32]>,
+	pub issuer: Option<BoundedVec<u8, ConstU32<128>>>,
+}
+
+/// Latest certificate reported for a member service.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct CertificateStatus<BlockNumber> {
+	pub report: CertificateReport,
+	pub reported_at: BlockNumber,
+	/// Whether the certificate differs from the expected certificate.
+	pub mismatch: bool,
+	/// Whether `CertificateExpiringSoon` was emitted for this certificate.
+	pub expiry_warned: bool,
+}
+
+/// Kinds of security incidents.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum SecurityIncidentKind {
+	/// An endpoint presented a certificate other than the expected one.
+	CertificateMismatch { fingerprint: [u8; 32] },
+}
+
+/// Heights a member service reported within a health check window, the highest across monitors.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct ReportedHeights {
+	pub member_service_id: u32,
+	pub best_block: u32,
+	pub finalized_block: Option<u32>,
+}
+
+/// Block heights reported for the member services of a service in one health check window, i.e.
+/// `block_number / HealthCheckInterval`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct HeightWindow<BlockNumber> {
+	pub window: BlockNumber,
+	pub heights: BoundedVec<ReportedHeights, ConstU32<MAX_HEIGHT_REPORTS>>,
+}
+
+/// Failed health checks of a member service in a row, from any monitor.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct FailureStreak<AccountId, BlockNumber> {
+	pub failures: u32,
+	pub started_at: BlockNumber,
+	/// Monitors that reported the failures. Monitors beyond the bound are left out.
+	pub monitors: BoundedVec<AccountId, ConstU32<16>>,
+	/// Most severe failure reported.
+	pub peak_failure: FailureCode,
+}
+
+impl<AccountId: PartialEq, BlockNumber> FailureStreak<AccountId, BlockNumber> {
+	fn note_failure(&mut self, monitor: AccountId, code: FailureCode) {
+		self.failures.saturating_inc();
+		if !self.monitors.contains(&monitor) {
+			let _ = self.monitors.try_push(monitor);
+		}
+		if code.severity() > self.peak_failure.severity() {
+			self.peak_failure = code;
+		}
+	}
+}
+
+/// An outage of a member service, opened after `IncidentThreshold` failed health checks in a row
+/// and closed by the next successful one.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Incident<AccountId, BlockNumber> {
+	pub id: u32,
+	pub service_id: u32,
+	pub member_service_id: u32,
+	/// The failures so far, starting with the first of the streak that opened the incident.
+	pub streak: FailureStreak<AccountId, BlockNumber>,
+	/// Block of the health check that closed the incident.
+	pub ended_at: Option<BlockNumber>,
+	/// Post-mortem of the member, e.g. a URL or the hash of a document.
+	pub post_mortem: Option<BoundedVec<u8, ConstU32<256>>>,
+}
+
+/// A failed health check that counted against a member service, kept so that it can be disputed.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct FailedReport<BlockNumber> {
+	/// Timestamp of the health check, which identifies it among the monitor's checks.
+	pub timestamp: u64,
+	pub block_number: BlockNumber,
+	/// Whether a dispute was opened for the report.
+	pub disputed: bool,
+}
+
+/// Failed health checks a dispute contests.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum DisputeTarget<AccountId> {
+	/// Failed health checks of one monitor, by timestamp.
+	Reports { monitor: AccountId, timestamps: BoundedVec<u64, ConstU32<16>> },
+	/// The failed health checks of an incident.
+	Incident(u32),
+}
+
+/// A member's claim that monitors reported a member service down incorrectly.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Dispute<AccountId, Balance, BlockNumber> {
+	pub member_service_id: u32,
+	pub disputant: AccountId,
+	pub target: DisputeTarget<AccountId>,
+	/// Monitors and timestamps of the reports the target referred to when it was opened.
+	pub reports: BoundedVec<(AccountId, u64), ConstU32<64>>,
+	/// Reserved from the disputant, slashed if the dispute is rejected.
+	pub bond: Balance,
+	pub opened_at: BlockNumber,
+	/// Ids of the members that voted to uphold the dispute, which follow membership transfers.
+	pub ayes: BoundedVec<u32, ConstU32<32>>,
+	/// Ids of the members that voted to reject the dispute.
+	pub nays: BoundedVec<u32, ConstU32<32>>,
+}
+
+/// Deposit reserved for a member service, slashed for persistent SLO breaches.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct MemberServiceDeposit<AccountId, Balance> {
+	pub depositor: AccountId,
+	pub amount: Balance,
+}
+
+/// A slash of a member service deposit, applied unless cancelled within `SlashDeferDuration` eras.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct UnappliedSlash<Balance> {
+	pub member_service_id: u32,
+	pub amount: Balance,
+}
+
+/// Health statistics of a member service over one era.
+#[derive(Clone, Default, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct EraRollup {
+	/// Health checks submitted.
+	pub checks: u32,
+	/// Health checks that found the member service up.
+	pub up_checks: u32,
+	/// Sum of the response times of the up checks.
+	pub total_response_time_ms: u64,
+	/// Windows in which the member service's heights were compared with the median.
+	pub height_windows: u32,
+	/// Windows in which the member service lagged beyond `MaxBlockLag`; it counts as degraded
+	/// in those.
+	pub lagging_windows: u32,
+	/// Sum of the best block lag over all compared windows.
+	pub total_lag: u64,
+	/// Largest best block lag in a compared window.
+	pub max_lag: u32,
+}
+
+impl EraRollup {
+	/// Share of the era the member service was up and in sync: the share of up checks, reduced by
+	/// the share of compared height windows it lagged in.
+	pub fn uptime(&self) -> Perbill {
+		let up = Perbill::from_rational(self.up_checks, self.checks.max(1));
+		let in_sync = match self.height_windows {
+			0 => Perbill::one(),
+			windows =>
+				Perbill::from_rational(windows.saturating_sub(self.lagging_windows), windows),
+		};
+		up * in_sync
+	}
+}
+
+/// Health check signed by a monitor key, submitted as an unsigned transaction. `public` must be
+/// bound to a monitor with `set_monitor_key`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct HealthCheckPayload<Public, BlockNumber> {
+	pub public: Public,
+	pub block_number: BlockNumber,
+	pub health_check: HealthCheck,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for HealthCheckPayload<T::Public, T::BlockNumber> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
+}
+
+/// Attestation that the token of a member service's domain challenge is published, signed by a
+/// monitor key and submitted as an unsigned transaction.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct DomainAttestationPayload<Public, BlockNumber> {
+	pub public: Public,
+	pub block_number: BlockNumber,
+	pub member_service_id: u32,
+	/// Token the monitor found published.
+	pub token: H256,
+}
+
+impl<T: SigningTypes> SignedPayload<T> for DomainAttestationPayload<T::Public, T::BlockNumber> {
+	fn public(&self) -> T::Public {
+		self.public.clone()
+	}
 }
 
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(12);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config:
+		SendTransactionTypes<Call<Self>> + SigningTypes + frame_system::Config
+	{
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		/// Type representing the weight of this pallet
 		type WeightInfo: WeightInfo;
 		type Currency: ReservableCurrency<Self::AccountId>;
+		/// Origin that suspends and resumes member services and sets how the member budget is
+		/// shared.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// Origin that registers networks and services, sets their parameters and sets the
+		/// verification status of member services.
+		type RegistrarOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// Origin that resolves disputes and cancels deferred slashes.
+		type SlashOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		/// Origin that dispatches calls as root with `dispatch_as_root`.
+		type RootDispatchOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+		type RuntimeCall: Parameter
+			+ UnfilteredDispatchable<RuntimeOrigin = Self::RuntimeOrigin>
+			+ Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
+			+ GetDispatchInfo;
+		/// Notified of the full set of members whenever a member joins or leaves.
+		type MembersChanged: ChangeMembers<Self::AccountId>;
+		/// Maximum number of members. At most the number of members `MembersChanged` can hold.
+		#[pallet::constant]
+		type MaxMembers: Get<u32>;
+		/// Amount reserved from an applicant for membership until the application is decided on
+		/// or expires.
+		#[pallet::constant]
+		type ApplicationDeposit: Get<BalanceOf<Self>>;
+		/// Number of blocks after which an application that wasn't decided on expires.
+		#[pallet::constant]
+		type ApplicationTimeout: Get<Self::BlockNumber>;
+		/// Number of member votes either way that decide on an application.
+		#[pallet::constant]
+		type ApplicationVotesRequired: Get<u32>;
+		/// Highest level a member can hold.
+		#[pallet::constant]
+		type MaxMemberLevel: Get<u8>;
+		/// Number of eras in a row of meeting the SLO with all its member services after which a
+		/// member is promoted a level. Zero disables automatic promotion.
+		#[pallet::constant]
+		type PromotionEras: Get<u32>;
+		/// Number of eras in a row of missing the SLO with any of its member services after which
+		/// a member is demoted a level. Zero disables automatic demotion.
+		#[pallet::constant]
+		type DemotionEras: Get<u32>;
+		type IdentityVerifier: VerifyIdentity<Self::AccountId>;
+		/// Whether applicants need a verified identity to be approved.
+		#[pallet::constant]
+		type RequireIdentityForApproval: Get<bool>;
+		/// Whether members need a verified identity to be promoted.
+		#[pallet::constant]
+		type RequireIdentityForPromotion: Get<bool>;
 		#[pallet::constant]
 		type HealthCheckReward: Get<u64>;
+		/// Minimum number of blocks between two health checks of the same member service by the
+		/// same monitor.
+		#[pallet::constant]
+		type HealthCheckInterval: Get<Self::BlockNumber>;
+		/// Crypto of the monitor keys that sign unsigned health checks.
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+		/// Priority of unsigned health check transactions.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+		/// How long the offchain worker waits for a member service to respond, in milliseconds.
+		#[pallet::constant]
+		type ProbeTimeout: Get<u64>;
+		/// Maximum number of member services the offchain worker probes per block.
+		#[pallet::constant]
+		type MaxProbesPerBlock: Get<u32>;
+		/// Time the offchain worker may spend probing per block, in milliseconds. Probes are only
+		/// started while at least `ProbeTimeout` of it is left.
+		#[pallet::constant]
+		type MaxProbeTimePerBlock: Get<u64>;
+		/// Number of blocks per era, the period health statistics are rolled up over.
+		#[pallet::constant]
+		type EraLength: Get<Self::BlockNumber>;
+		/// Number of eras the rollups are kept for.
+		#[pallet::constant]
+		type EraHistoryDepth: Get<u32>;
+		/// Number of blocks a member service may lag behind the median of the member services of
+		/// the same service before it counts as degraded.
+		#[pallet::constant]
+		type MaxBlockLag: Get<u32>;
+		/// Number of days before a certificate expires that `CertificateExpiringSoon` is emitted.
+		#[pallet::constant]
+		type CertificateExpiryWarningDays: Get<u32>;
+		/// Number of monitors that must find a domain challenge's token published before the
+		/// member service is verified. At most 16.
+		#[pallet::constant]
+		type RequiredAttestations: Get<u32>;
+		/// Number of maintenance windows a member service may schedule to start in one era.
+		#[pallet::constant]
+		type MaxMaintenancesPerEra: Get<u32>;
+		/// Number of blocks a maintenance window may last.
+		#[pallet::constant]
+		type MaxMaintenanceDuration: Get<Self::BlockNumber>;
+		/// Number of blocks before its start that a maintenance window must be scheduled.
+		#[pallet::constant]
+		type MaintenanceNotice: Get<Self::BlockNumber>;
+		/// Number of failed health checks in a row that open an incident.
+		#[pallet::constant]
+		type IncidentThreshold: Get<u32>;
+		/// Number of most recent incidents kept. Older ones are removed once closed.
+		#[pallet::constant]
+		type MaxIncidentHistory: Get<u32>;
+		/// Amount reserved from a member disputing health checks.
+		#[pallet::constant]
+		type DisputeBond: Get<BalanceOf<Self>>;
+		/// Number of member votes for either outcome that resolve a dispute.
+		#[pallet::constant]
+		type DisputeVotesRequired: Get<u32>;
+		/// Number of blocks after which a dispute that wasn't resolved expires and its bond is
+		/// refunded.
+		#[pallet::constant]
+		type DisputeTimeout: Get<Self::BlockNumber>;
+		/// Amount reserved from a member for each member service it registers, until it
+		/// deregisters it.
+		#[pallet::constant]
+		type MemberServiceDeposit: Get<BalanceOf<Self>>;
+		/// Maximum number of registered member services, which bounds the work done when an era
+		/// starts.
+		#[pallet::constant]
+		type MaxMemberServices: Get<u32>;
+		/// Id of the account that slashes are paid into and rewards can be paid from.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Share of health checks a member service must pass in an era to meet its SLO.
+		#[pallet::constant]
+		type SloUptime: Get<Perbill>;
+		/// Number of eras in a row of SLO breach after which a member service's deposit is slashed
+		/// each era. Fewer breaches only emit warnings.
+		#[pallet::constant]
+		type SlashAfterBreaches: Get<u32>;
+		/// Number of eras in a row of SLO breach after which a member service is suspended.
+		#[pallet::constant]
+		type SuspendAfterBreaches: Get<u32>;
+		/// Share of a member service's deposit slashed per era of breach.
+		#[pallet::constant]
+		type BreachSlash: Get<Perbill>;
+		/// Number of eras a slash is deferred by, during which it can be cancelled.
+		#[pallet::constant]
+		type SlashDeferDuration: Get<u32>;
+		/// Amount shared between member services for each era, by service weight. Claims are paid
+		/// out of the slashes in the reward pot and minted beyond them.
+		#[pallet::constant]
+		type MemberEraBudget: Get<BalanceOf<Self>>;
+		/// Mean response time in milliseconds up to which a member service gets its full latency
+		/// score.
+		#[pallet::constant]
+		type TargetResponseTime: Get<u32>;
 	}
 
 	#[pallet::event]
@@ -95,99 +835,614 @@ pub mod pallet {
 			who: T::AccountId,
 			name: BoundedVec<u8, ConstU32<32>>,
 		},
+		MonitorKeySet {
+			monitor: T::AccountId,
+			key: T::AccountId,
+		},
 		HealthCheckSubmitted {
 			member_service_name: BoundedVec<u8, ConstU32<64>>,
 			monitor_name: BoundedVec<u8, ConstU32<32>>,
 		},
-	}
-
-	#[pallet::error]
-	pub enum Error<T> {
-		ServiceAlreadyRegistered,
-		MemberAlreadyRegistered,
-		InvalidMemberName,
-		MemberServiceAlreadyRegistered,
-		ServiceNotFound,
-		MemberNotFound,
-		InvalidIP4Address,
-		InvalidPort,
-		MonitorAlreadyRegistered,
-		MemberServiceNotFound,
-		MonitorNotFound,
-	}
-
-	#[pallet::storage]
-	pub(super) type ServiceCount<T: Config> = StorageValue<_, u32>;
-
-	#[pallet::storage]
-	pub(super) type Services<T: Config> = StorageMap<_, Blake2_128Concat, u32, Service>;
-
-	#[pallet::storage]
-	pub(super) type MemberCount<T: Config> = StorageValue<_, u32>;
-
-	#[pallet::storage]
-	pub(super) type Members<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, Member>;
-
-	#[pallet::storage]
-	pub(super) type MemberServiceCount<T: Config> = StorageValue<_, u32>;
-
-	#[pallet::storage]
-	pub(super) type MemberServices<T: Config> = StorageMap<_, Blake2_128Concat, u32, MemberService>;
-
-	#[pallet::storage]
-	pub(super) type Monitors<T: Config> =
-		StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<u8, ConstU32<32>>>;
-
-	#[pallet::storage]
-	pub(super) type HealthChecks<T: Config> = StorageDoubleMap<
-		_,
-		Blake2_128Concat,
-		u32,
-		Blake2_128Concat,
-		T::AccountId,
-		BoundedVec<HealthCheck, ConstU32<512>>,
-	>;
-
-	#[pallet::call]
-	impl<T: Config> Pallet<T> {
-		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::dummy_weight())]
-		pub fn register_service(
-			origin: OriginFor<T>,
-			ty: ServiceType,
+		NetworkRegistered {
+			id: u32,
 			name: BoundedVec<u8, ConstU32<64>>,
-			url_path: BoundedVec<u8, ConstU32<32>>,
-		) -> DispatchResult {
-			ensure_root(origin)?;
-			let id = match ServiceCount::<T>::get() {
-				Some(count) => count,
-				None => 0,
-			};
-			ServiceCount::<T>::set(Some(id + 1));
-			ensure!(!Services::<T>::contains_key(&id), Error::<T>::ServiceAlreadyRegistered);
-			let service = Service { id, ty, name: name.clone(), url_path };
-			Services::<T>::insert(&id, service);
-			Self::deposit_event(Event::ServiceRegistered { id, name });
-			Ok(())
+		},
+		ServiceNetworkSet {
+			service_id: u32,
+			network_id: u32,
+		},
+		ProbeParamsSet {
+			service_id: u32,
+		},
+		ArchiveBlockSet {
+			network_id: u32,
+			block_hash: Option<H256>,
+		},
+		ExpectedCertificateSet {
+			member_service_id: u32,
+		},
+		/// A member service's certificate expires within `CertificateExpiryWarningDays`. Emitted
+		/// once per certificate.
+		CertificateExpiringSoon {
+			member_service_id: u32,
+			expires_at: u64,
+		},
+		/// A monitor observed something that may mean a member service has been compromised.
+		SecurityIncident {
+			member_service_id: u32,
+			monitor: T::AccountId,
+			kind: SecurityIncidentKind,
+		},
+		BootNodeSet {
+			member_service_id: u32,
+			peer_id: BoundedVec<u8, ConstU32<64>>,
+		},
+		/// The member has to publish `token` to get the member service verified.
+		DomainChallengeIssued {
+			member_service_id: u32,
+			token: H256,
+		},
+		DomainAttested {
+			member_service_id: u32,
+			monitor: T::AccountId,
+		},
+		VerificationStatusSet {
+			member_service_id: u32,
+			status: VerificationStatus,
+		},
+		IncidentOpened {
+			id: u32,
+			member_service_id: u32,
+			peak_failure: FailureCode,
+		},
+		IncidentClosed {
+			id: u32,
+			member_service_id: u32,
+			started_at: T::BlockNumber,
+			ended_at: T::BlockNumber,
+		},
+		PostMortemSet {
+			incident_id: u32,
+		},
+		HealthCheckDisputed {
+			id: u32,
+			member_service_id: u32,
+			disputant: T::AccountId,
+			reports: u32,
+		},
+		DisputeVoted {
+			id: u32,
+			voter: T::AccountId,
+			uphold: bool,
+		},
+		/// A member service missed its SLO for `consecutive` eras in a row, which is a warning
+		/// until `SlashAfterBreaches`.
+		SloBreached {
+			member_service_id: u32,
+			era: u32,
+			uptime: Perbill,
+			consecutive: u32,
+		},
+		SlashDeferred {
+			member_service_id: u32,
+			amount: BalanceOf<T>,
+			apply_era: u32,
+		},
+		SlashApplied {
+			member_service_id: u32,
+			amount: BalanceOf<T>,
+		},
+		SlashCancelled {
+			era: u32,
+			member_service_id: u32,
+			amount: BalanceOf<T>,
+		},
+		/// Health checks of the member service are rejected until it is resumed.
+		MemberServiceSuspended {
+			member_service_id: u32,
+		},
+		MemberServiceResumed {
+			member_service_id: u32,
+		},
+		/// A member was credited `amount` for running a member service in `era`, which is its
+		/// share of `MemberEraBudget` scaled by `uptime` and `latency_score`.
+		MemberPaid {
+			member: T::AccountId,
+			member_service_id: u32,
+			era: u32,
+			uptime: Perbill,
+			latency_score: Perbill,
+			service_weight: u32,
+			level: u8,
+			amount: BalanceOf<T>,
+		},
+		RewardsClaimed {
+			member: T::AccountId,
+			amount: BalanceOf<T>,
+		},
+		ServiceWeightSet {
+			service_id: u32,
+			weight: u32,
+		},
+		RootDispatched {
+			result: DispatchResult,
+		},
+		MembershipApplied {
+			applicant: T::AccountId,
+			name: BoundedVec<u8, ConstU32<64>>,
+		},
+		ApplicationVoted {
+			applicant: T::AccountId,
+			voter: T::AccountId,
+			approve: bool,
+		},
+		ApplicationApproved {
+			applicant: T::AccountId,
+		},
+		ApplicationRejected {
+			applicant: T::AccountId,
+		},
+		ApplicationExpired {
+			applicant: T::AccountId,
+		},
+		MemberPromoted {
+			member: T::AccountId,
+			level: u8,
+		},
+		MemberDemoted {
+			member: T::AccountId,
+			level: u8,
+		},
+		ServiceMinLevelSet {
+			service_id: u32,
+			min_level: u8,
+		},
+		MembershipTransferProposed {
+			from: T::AccountId,
+			to: T::AccountId,
+		},
+		/// The membership of `from`, with its deposits, monitors, member services and pending
+		/// rewards, moved to `to`.
+		MembershipTransferred {
+			from: T::AccountId,
+			to: T::AccountId,
+		},
+		/// Members must seal their contacts to the new contact key.
+		ContactKeyRotated {
+			key_id: u32,
+			public: [u8; 32],
+		},
+		ContactUpdated {
+			member_id: u32,
+			key_id: u32,
+			version: u32,
+		},
+		ContactRemoved {
+			member_id: u32,
+		},
+		/// An upheld dispute removed the reports from the rollups and reversed the monitors'
+		/// rewards for them. A rejected one slashed the bond.
+		DisputeResolved {
+			id: u32,
+			upheld: bool,
+		},
+		/// Health checks of a member service from `start` up to `end` won't count against it.
+		MaintenanceScheduled {
+			member_service_id: u32,
+			start: T::BlockNumber,
+			end: T::BlockNumber,
+			reason: BoundedVec<u8, ConstU32<128>>,
+		},
+		/// A member service's best or finalized block lagged behind the median of its service by
+		/// more than `MaxBlockLag` in a health check window.
+		ServiceLagging {
+			service_id: u32,
+			member_service_id: u32,
+			best_lag: u32,
+			finalized_lag: u32,
+		},
+		/// A dispute wasn't resolved within `DisputeTimeout`. Its bond was refunded and its
+		/// reports stand.
+		DisputeExpired {
+			id: u32,
+		},
+		/// A member service was deregistered and the rest of its deposit released.
+		MemberServiceDeregistered {
+			member_service_id: u32,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		ServiceAlreadyRegistered,
+		MemberAlreadyRegistered,
+		InvalidMemberName,
+		MemberServiceAlreadyRegistered,
+		ServiceNotFound,
+		MemberNotFound,
+		InvalidIP4Address,
+		InvalidPort,
+		MonitorAlreadyRegistered,
+		MemberServiceNotFound,
+		MonitorNotFound,
+		HealthCheckTooFrequent,
+		NotMonitorOwner,
+		MonitorKeyInUse,
+		NetworkNotFound,
+		InvalidNetworkName,
+		NotMemberServiceOwner,
+		NotBootNodeService,
+		InvalidMultiaddr,
+		MemberServiceNotVerified,
+		MemberServiceAlreadyVerified,
+		InvalidChallengeToken,
+		AlreadyAttested,
+		/// Monitors can't attest the member services of the member that registered them.
+		SelfAttestation,
+		TooManyAttestations,
+		/// The window ends before it starts or lasts longer than `MaxMaintenanceDuration`.
+		InvalidMaintenanceWindow,
+		MaintenanceNoticeTooShort,
+		TooManyMaintenances,
+		IncidentNotFound,
+		ReportNotFound,
+		ReportAlreadyDisputed,
+		NoReportsToDispute,
+		DisputeNotFound,
+		AlreadyVoted,
+		/// Disputants can't vote on their own disputes.
+		OwnDispute,
+		TooManyVotes,
+		MemberServiceSuspended,
+		MemberServiceNotSuspended,
+		EmptyTargets,
+		InvalidSlashIndex,
+		NoPendingRewards,
+		ApplicationNotFound,
+		AlreadyApplied,
+		TooManyApplications,
+		InvalidLevel,
+		MemberLevelTooLow,
+		TransferNotFound,
+		TransferToSelf,
+		IdentityNotVerified,
+		NoContactKey,
+		/// The contact is sealed to a key other than the current contact key.
+		StaleContactKey,
+		ContactNotFound,
+		/// Monitors can't probe the service's type with the given probe parameters.
+		UnsupportedProbeParams,
+		TooManyDisputes,
+		TooManyMemberServices,
+		/// A slash of the member service is yet to be applied.
+		SlashPending,
+		TooManyMembers,
+		TooManyMonitors,
+		/// All dispute ids have been used.
+		DisputeIdOverflow,
+		/// Less than a deposit or bond being moved is still reserved.
+		ReserveTooLow,
+	}
+
+	#[pallet::storage]
+	pub(super) type ServiceCount<T: Config> = StorageValue<_, u32>;
+
+	#[pallet::storage]
+	pub(super) type Services<T: Config> = StorageMap<_, Blake2_128Concat, u32, Service>;
+
+	#[pallet::storage]
+	pub(super) type NetworkCount<T: Config> = StorageValue<_, u32>;
+
+	#[pallet::storage]
+	pub(super) type Networks<T: Config> = StorageMap<_, Blake2_128Concat, u32, Network>;
+
+	#[pallet::storage]
+	pub(super) type MemberCount<T: Config> = StorageValue<_, u32>;
+
+	#[pallet::storage]
+	pub(super) type Members<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, Member>;
+
+	#[pallet::storage]
+	pub(super) type MemberServiceCount<T: Config> = StorageValue<_, u32>;
+
+	#[pallet::storage]
+	pub(super) type MemberServices<T: Config> = StorageMap<_, Blake2_128Concat, u32, MemberService>;
+
+	/// Number of registered member services, up to `MaxMemberServices`.
+	#[pallet::storage]
+	pub(super) type ActiveMemberServices<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type VerificationStatuses<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, VerificationStatus, ValueQuery>;
+
+	/// Open domain challenges of unverified member services.
+	#[pallet::storage]
+	pub(super) type DomainChallenges<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, DomainChallenge<T::AccountId, T::BlockNumber>>;
+
+	/// Current and upcoming maintenance windows per member service.
+	#[pallet::storage]
+	pub(super) type MaintenanceWindows<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		u32,
+		BoundedVec<MaintenanceWindow<T::BlockNumber>, ConstU32<16>>,
+		ValueQuery,
+	>;
+
+	/// Number of maintenance windows scheduled to start per era and member service.
+	#[pallet::storage]
+	pub(super) type MaintenanceCounts<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, u32, Blake2_128Concat, u32, u32, ValueQuery>;
+
+	/// Failed health checks in a row per member service without an open incident.
+	#[pallet::storage]
+	pub(super) type FailureStreaks<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, FailureStreak<T::AccountId, T::BlockNumber>>;
+
+	#[pallet::storage]
+	pub(super) type IncidentCount<T: Config> = StorageValue<_, u32>;
+
+	/// The last `MaxIncidentHistory` incidents, and older ones still open.
+	#[pallet::storage]
+	pub(super) type Incidents<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, Incident<T::AccountId, T::BlockNumber>>;
+
+	/// Open incident per member service.
+	#[pallet::storage]
+	pub(super) type OpenIncidents<T: Config> = StorageMap<_, Blake2_128Concat, u32, u32>;
+
+	/// Recent failed health checks per member service and monitor that counted in the rollups.
+	#[pallet::storage]
+	pub(super) type FailedReports<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		u32,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<FailedReport<T::BlockNumber>, ConstU32<64>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type DisputeCount<T: Config> = StorageValue<_, u32>;
+
+	/// Open disputes.
+	#[pallet::storage]
+	pub(super) type Disputes<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, Dispute<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+
+	/// Open disputes of each disputant.
+	#[pallet::storage]
+	pub(super) type DisputesByDisputant<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<u32, ConstU32<16>>, ValueQuery>;
+
+	/// Disputes that expire at each block.
+	#[pallet::storage]
+	pub(super) type DisputeExpiries<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<u32, ConstU32<64>>, ValueQuery>;
+
+	/// Number of upheld disputes against each monitor, which lower its reputation.
+	#[pallet::storage]
+	pub(super) type MonitorStrikes<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type MemberServiceDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, MemberServiceDeposit<T::AccountId, BalanceOf<T>>>;
+
+	/// Member services whose deposit each account holds.
+	#[pallet::storage]
+	pub(super) type DepositsByDepositor<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<u32, T::MaxMemberServices>,
+		ValueQuery,
+	>;
+
+	/// Number of eras in a row each member service missed its SLO in.
+	#[pallet::storage]
+	pub(super) type BreachStreaks<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, u32, ValueQuery>;
+
+	/// Slashes to apply at the start of each era.
+	#[pallet::storage]
+	pub(super) type UnappliedSlashes<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		u32,
+		BoundedVec<UnappliedSlash<BalanceOf<T>>, ConstU32<256>>,
+		ValueQuery,
+	>;
+
+	#[pallet::type_value]
+	pub(super) fn DefaultServiceWeight() -> u32 {
+		1
+	}
+
+	/// Relative share of `MemberEraBudget` each member service of a service earns.
+	#[pallet::storage]
+	pub(super) type ServiceWeights<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, u32, ValueQuery, DefaultServiceWeight>;
+
+	/// Member payouts not claimed yet, paid from the reward pot.
+	#[pallet::storage]
+	pub(super) type PendingRewards<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// Account each member proposed to transfer its membership to, until that account accepts.
+	#[pallet::storage]
+	pub(super) type PendingTransfers<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+	/// Key of the admin group that member contacts are sealed to.
+	#[pallet::storage]
+	pub(super) type CurrentContactKey<T: Config> = StorageValue<_, ContactKey>;
+
+	/// Sealed contact details of each member, by member id.
+	#[pallet::storage]
+	pub(super) type Contacts<T: Config> = StorageMap<_, Twox64Concat, u32, EncryptedContact>;
+
+	/// Suspended member services, with the block they were suspended at.
+	#[pallet::storage]
+	pub(super) type SuspendedMemberServices<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, T::BlockNumber>;
+
+	/// Pending applications for membership.
+	#[pallet::storage]
+	pub(super) type Applications<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Application<BalanceOf<T>, T::BlockNumber>>;
+
+	#[pallet::storage]
+	pub(super) type SlaStreaks<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, SlaStreak, ValueQuery>;
+
+	/// Applicants whose applications expire at each block.
+	#[pallet::storage]
+	pub(super) type ApplicationExpiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<T::AccountId, ConstU32<64>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type Monitors<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<u8, ConstU32<32>>>;
+
+	/// Member account that registered each monitor.
+	#[pallet::storage]
+	pub(super) type MonitorOwners<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+	/// Monitors each member registered.
+	#[pallet::storage]
+	pub(super) type MonitorsByOwner<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<T::AccountId, ConstU32<16>>,
+		ValueQuery,
+	>;
+
+	/// Monitor key (as an account) currently bound to each monitor.
+	#[pallet::storage]
+	pub(super) type MonitorKeyOf<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+	/// Monitor each bound monitor key signs health checks for.
+	#[pallet::storage]
+	pub(super) type MonitorKeys<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+	#[pallet::storage]
+	pub(super) type HealthChecks<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		u32,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<HealthCheck, ConstU32<512>>,
+	>;
+
+	/// Block of the last health check per member service and monitor, used for rate limiting.
+	#[pallet::storage]
+	pub(super) type LastHealthCheck<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, u32, Blake2_128Concat, T::AccountId, T::BlockNumber>;
+
+	/// Heights reported in the current health check window, per service. A window is evaluated
+	/// when the next window starts, or when the first height of the next window is reported.
+	#[pallet::storage]
+	pub(super) type HeightWindows<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, HeightWindow<T::BlockNumber>>;
+
+	/// Health statistics per era and member service.
+	#[pallet::storage]
+	pub(super) type EraRollups<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, u32, Blake2_128Concat, u32, EraRollup, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type ExpectedCertificates<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, ExpectedCertificate>;
+
+	#[pallet::storage]
+	pub(super) type CertificateStatuses<T: Config> =
+		StorageMap<_, Blake2_128Concat, u32, CertificateStatus<T::BlockNumber>>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Expire the applications and disputes due at this block, and evaluate the height windows
+		/// that closed when a health check window starts. When an era starts, also pay
+		/// members for the era that ended, review their levels, apply the penalties for SLO
+		/// breaches in it and the slashes deferred to this era, and drop the rollups of eras
+		/// older than `EraHistoryDepth`. `MaxMemberServices` bounds the work of an era start.
+		fn on_initialize(block_number: T::BlockNumber) -> Weight {
+			let mut weight = Self::expire_applications(block_number);
+			weight.saturating_accrue(Self::expire_disputes(block_number));
+			if (block_number % T::HealthCheckInterval::get().max(One::one())).is_zero() {
+				weight.saturating_accrue(Self::close_height_windows(block_number));
+			}
+			let era_length = T::EraLength::get().max(One::one());
+			if !(block_number % era_length).is_zero() {
+				return weight
+			}
+			let era = Self::era_of(block_number);
+			if let Some(ended) = era.checked_sub(1) {
+				weight.saturating_accrue(Self::pay_members(ended));
+				weight.saturating_accrue(Self::review_levels(ended));
+				weight.saturating_accrue(Self::penalize_breaches(ended, era));
+			}
+			weight.saturating_accrue(Self::apply_slashes(era));
+			if let Some(expired) = era.checked_sub(T::EraHistoryDepth::get()) {
+				let removed = EraRollups::<T>::clear_prefix(expired, u32::MAX, None);
+				let counts = MaintenanceCounts::<T>::clear_prefix(expired, u32::MAX, None);
+				weight.saturating_accrue(
+					T::DbWeight::get().writes((removed.backend + counts.backend).into()),
+				);
+			}
+			weight
+		}
+
+		/// Probe member services if this node holds a bound monitor key.
+		fn offchain_worker(block_number: T::BlockNumber) {
+			Self::probe_member_services(block_number);
 		}
+	}
 
-		#[pallet::call_index(1)]
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::call_index(0)]
 		#[pallet::weight(T::WeightInfo::dummy_weight())]
-		pub fn register_member(
+		pub fn register_service(
 			origin: OriginFor<T>,
+			ty: ServiceType,
 			name: BoundedVec<u8, ConstU32<64>>,
+			url_path: BoundedVec<u8, ConstU32<32>>,
+			network_id: u32,
 		) -> DispatchResult {
-			let sender = ensure_signed(origin)?;
-			let id = match MemberCount::<T>::get() {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			ensure!(Networks::<T>::contains_key(network_id), Error::<T>::NetworkNotFound);
+			let id = match ServiceCount::<T>::get() {
 				Some(count) => count,
 				None => 0,
 			};
-			MemberCount::<T>::set(Some(id + 1));
-			ensure!(!Members::<T>::contains_key(&sender), Error::<T>::MemberAlreadyRegistered);
-			ensure!(!name.is_empty(), Error::<T>::InvalidMemberName);
-			let member = Member { id, name: name.clone() };
-			Members::<T>::insert(&sender, member);
-			Self::deposit_event(Event::MemberRegistered { account_id: sender.clone(), id, name });
+			ServiceCount::<T>::set(Some(id + 1));
+			ensure!(!Services::<T>::contains_key(&id), Error::<T>::ServiceAlreadyRegistered);
+			let probe = ProbeParams::default_for(&ty);
+			let service = Service {
+				id,
+				ty,
+				name: name.clone(),
+				url_path,
+				network_id: Some(network_id),
+				probe,
+				min_level: 0,
+			};
+			Services::<T>::insert(&id, service);
+			Self::deposit_event(Event::ServiceRegistered { id, name });
 			Ok(())
 		}
 
@@ -203,6 +1458,9 @@ pub mod pallet {
 			let sender = ensure_signed(origin)?;
 			let service = Services::<T>::get(&service_id).ok_or(Error::<T>::ServiceNotFound)?;
 			let member = Members::<T>::get(&sender).ok_or(Error::<T>::MemberNotFound)?;
+			ensure!(member.level >= service.min_level, Error::<T>::MemberLevelTooLow);
+			let active = ActiveMemberServices::<T>::get();
+			ensure!(active < T::MaxMemberServices::get(), Error::<T>::TooManyMemberServices);
 			let id = match MemberServiceCount::<T>::get() {
 				Some(count) => count,
 				None => 0,
@@ -213,6 +1471,14 @@ pub mod pallet {
 				Error::<T>::MemberServiceAlreadyRegistered,
 			);
 			ensure!(!address.is_empty(), Error::<T>::InvalidIP4Address,);
+			let deposit = T::MemberServiceDeposit::get();
+			T::Currency::reserve(&sender, deposit)?;
+			DepositsByDepositor::<T>::try_mutate(&sender, |ids| ids.try_push(id))
+				.map_err(|_| Error::<T>::TooManyMemberServices)?;
+			MemberServiceDeposits::<T>::insert(
+				id,
+				MemberServiceDeposit { depositor: sender.clone(), amount: deposit },
+			);
 			let member_service = MemberService {
 				service_id: service.id,
 				member_id: member.id,
@@ -220,14 +1486,17 @@ pub mod pallet {
 				name: name.clone(),
 				address: address.clone(),
 				port,
+				bootnode: None,
 			};
 			MemberServices::<T>::insert(&id, member_service);
+			ActiveMemberServices::<T>::put(active + 1);
 			Self::deposit_event(Event::MemberServiceRegistered {
 				service_id: service.id,
 				member_id: member.id,
 				id,
 				name: name.clone(),
 			});
+			Self::issue_domain_challenge(id);
 			Ok(())
 		}
 
@@ -241,35 +1510,912 @@ pub mod pallet {
 			let sender = ensure_signed(origin)?;
 			ensure!(Members::<T>::contains_key(&sender), Error::<T>::MemberNotFound);
 			ensure!(!Monitors::<T>::contains_key(&monitor), Error::<T>::MonitorAlreadyRegistered);
+			MonitorsByOwner::<T>::try_mutate(&sender, |monitors| {
+				monitors.try_push(monitor.clone())
+			})
+			.map_err(|_| Error::<T>::TooManyMonitors)?;
 			Monitors::<T>::insert(&monitor, name.clone());
+			MonitorOwners::<T>::insert(&monitor, &sender);
 			Self::deposit_event(Event::MonitorRegistered { who: sender, name });
 			Ok(())
 		}
 
+		/// Submit a health check for a member service.
+		///
+		/// Free for registered monitors. Spam is kept out of the pool by [`CheckHealthCheck`],
+		/// which allows one check per member service and monitor per `HealthCheckInterval`.
 		#[pallet::call_index(4)]
-		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		#[pallet::weight((Pallet::<T>::health_check_weight(), DispatchClass::Normal, Pays::No))]
 		pub fn submit_health_check(
+			origin: OriginFor<T>,
+			health_check: HealthCheck,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::do_submit_health_check(sender, health_check)
+		}
+
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::WeightInfo::zero_weight())]
+		pub fn mint(origin: OriginFor<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let reward: BalanceOf<T> = T::HealthCheckReward::get().saturated_into::<BalanceOf<T>>();
+			T::Currency::deposit_creating(&sender, reward);
+			Ok(())
+		}
+
+		/// Submit a health check signed by a monitor key, without a transaction fee.
+		///
+		/// The monitor is the one the key that signed `payload` is bound to. Validated in
+		/// `ValidateUnsigned`, which allows one check per member service and monitor per
+		/// `HealthCheckInterval` window.
+		#[pallet::call_index(6)]
+		#[pallet::weight(Pallet::<T>::health_check_weight())]
+		pub fn submit_health_check_unsigned(
+			origin: OriginFor<T>,
+			payload: HealthCheckPayload<T::Public, T::BlockNumber>,
+			_signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			let monitor =
+				Self::monitor_of_key(&payload.public).ok_or(Error::<T>::MonitorNotFound)?;
+			Self::do_submit_health_check(monitor, payload.health_check)
+		}
+
+		/// Bind a monitor key to `monitor`, replacing the key bound before. Binding the key that is
+		/// already bound to `monitor` is a no-op.
+		///
+		/// Callable by the member that registered the monitor or by the monitor itself, so keys can
+		/// be rotated without re-registering the monitor.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_monitor_key(
+			origin: OriginFor<T>,
+			monitor: T::AccountId,
+			key: T::Public,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Monitors::<T>::contains_key(&monitor), Error::<T>::MonitorNotFound);
+			ensure!(
+				sender == monitor || MonitorOwners::<T>::get(&monitor) == Some(sender),
+				Error::<T>::NotMonitorOwner,
+			);
+			let key = key.into_account();
+			if MonitorKeyOf::<T>::get(&monitor).as_ref() == Some(&key) {
+				return Ok(())
+			}
+			ensure!(!MonitorKeys::<T>::contains_key(&key), Error::<T>::MonitorKeyInUse);
+			if let Some(old_key) = MonitorKeyOf::<T>::get(&monitor) {
+				MonitorKeys::<T>::remove(&old_key);
+			}
+			MonitorKeys::<T>::insert(&key, &monitor);
+			MonitorKeyOf::<T>::insert(&monitor, &key);
+			Self::deposit_event(Event::MonitorKeySet { monitor, key });
+			Ok(())
+		}
+
+		/// Register a network that services can be bound to.
+		#[pallet::call_index(8)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn register_network(
+			origin: OriginFor<T>,
+			name: BoundedVec<u8, ConstU32<64>>,
+			genesis_hash: H256,
+			relay_network_id: Option<u32>,
+			para_id: Option<u32>,
+			ss58_prefix: u16,
+			token_symbol: BoundedVec<u8, ConstU32<8>>,
+		) -> DispatchResult {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			ensure!(!name.is_empty(), Error::<T>::InvalidNetworkName);
+			if let Some(relay_network_id) = relay_network_id {
+				ensure!(Networks::<T>::contains_key(relay_network_id), Error::<T>::NetworkNotFound);
+			}
+			let id = NetworkCount::<T>::get().unwrap_or_default();
+			NetworkCount::<T>::set(Some(id + 1));
+			let network = Network {
+				id,
+				name: name.clone(),
+				genesis_hash,
+				relay_network_id,
+				para_id,
+				ss58_prefix,
+				token_symbol,
+				archive_block_hash: None,
+			};
+			Networks::<T>::insert(id, network);
+			Self::deposit_event(Event::NetworkRegistered { id, name });
+			Ok(())
+		}
+
+		/// Bind a service to the network its endpoints must serve.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_service_network(
+			origin: OriginFor<T>,
+			service_id: u32,
+			network_id: u32,
+		) -> DispatchResult {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			ensure!(Networks::<T>::contains_key(network_id), Error::<T>::NetworkNotFound);
+			Services::<T>::try_mutate(service_id, |service| -> DispatchResult {
+				let service = service.as_mut().ok_or(Error::<T>::ServiceNotFound)?;
+				service.network_id = Some(network_id);
+				Ok(())
+			})?;
+			Self::deposit_event(Event::ServiceNetworkSet { service_id, network_id });
+			Ok(())
+		}
+
+		/// Replace the probe parameters a service was registered with.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_probe_params(
+			origin: OriginFor<T>,
+			service_id: u32,
+			probe: ProbeParams,
+		) -> DispatchResult {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			Services::<T>::try_mutate(service_id, |service| -> DispatchResult {
+				let service = service.as_mut().ok_or(Error::<T>::ServiceNotFound)?;
+				ensure!(probe.supports(&service.ty), Error::<T>::UnsupportedProbeParams);
+				service.probe = probe;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::ProbeParamsSet { service_id });
+			Ok(())
+		}
+
+		/// Set the multiaddr, including `/p2p/<peer_id>`, clients reach a bootnode member service
+		/// at.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_bootnode(
+			origin: OriginFor<T>,
+			member_service_id: u32,
+			multiaddr: BoundedVec<u8, ConstU32<256>>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let member = Members::<T>::get(&sender).ok_or(Error::<T>::MemberNotFound)?;
+			let peer_id: BoundedVec<u8, ConstU32<64>> =
+				crate::multiaddr::bootnode_peer_id(&multiaddr)
+					.and_then(|peer_id| peer_id.to_vec().try_into().ok())
+					.ok_or(Error::<T>::InvalidMultiaddr)?;
+			MemberServices::<T>::try_mutate(
+				member_service_id,
+				|member_service| -> DispatchResult {
+					let member_service =
+						member_service.as_mut().ok_or(Error::<T>::MemberServiceNotFound)?;
+					ensure!(
+						member_service.member_id == member.id,
+						Error::<T>::NotMemberServiceOwner
+					);
+					let service = Services::<T>::get(member_service.service_id)
+						.ok_or(Error::<T>::ServiceNotFound)?;
+					ensure!(service.ty.is_bootnode(), Error::<T>::NotBootNodeService);
+					member_service.bootnode =
+						Some(BootNodeAddress { multiaddr, peer_id: peer_id.clone() });
+					Ok(())
+				},
+			)?;
+			Self::deposit_event(Event::BootNodeSet { member_service_id, peer_id });
+			Ok(())
+		}
+
+		/// Set the old block whose state archive endpoints of a network are checked for, e.g.
+		/// block 1. `None` disables the archive check.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_archive_block(
+			origin: OriginFor<T>,
+			network_id: u32,
+			block_hash: Option<H256>,
+		) -> DispatchResult {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			Networks::<T>::try_mutate(network_id, |network| -> DispatchResult {
+				let network = network.as_mut().ok_or(Error::<T>::NetworkNotFound)?;
+				network.archive_block_hash = block_hash;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::ArchiveBlockSet { network_id, block_hash });
+			Ok(())
+		}
+
+		/// Set the certificate a member service is expected to present, or `None` to stop checking
+		/// it.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_expected_certificate(
+			origin: OriginFor<T>,
+			member_service_id: u32,
+			expected: Option<ExpectedCertificate>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_member_service_owner(&sender, member_service_id)?;
+			ExpectedCertificates::<T>::set(member_service_id, expected);
+			Self::deposit_event(Event::ExpectedCertificateSet { member_service_id });
+			Ok(())
+		}
+
+		/// Attest that the token of a member service's domain challenge is published.
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn attest_domain(
+			origin: OriginFor<T>,
+			member_service_id: u32,
+			token: H256,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::do_attest_domain(sender, member_service_id, token)
+		}
+
+		/// Attest a domain challenge with an attestation signed by a monitor key, without a
+		/// transaction fee.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn submit_domain_attestation_unsigned(
+			origin: OriginFor<T>,
+			payload: DomainAttestationPayload<T::Public, T::BlockNumber>,
+			_signature: T::Signature,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			let monitor =
+				Self::monitor_of_key(&payload.public).ok_or(Error::<T>::MonitorNotFound)?;
+			Self::do_attest_domain(monitor, payload.member_service_id, payload.token)
+		}
+
+		/// Verify a member service without a domain challenge, or revoke its verification and
+		/// issue a new challenge.
+		#[pallet::call_index(16)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_verification_status(
+			origin: OriginFor<T>,
+			member_service_id: u32,
+			status: VerificationStatus,
+		) -> DispatchResult {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			ensure!(
+				MemberServices::<T>::contains_key(member_service_id),
+				Error::<T>::MemberServiceNotFound,
+			);
+			match status {
+				VerificationStatus::Verified => {
+					VerificationStatuses::<T>::insert(member_service_id, status);
+					DomainChallenges::<T>::remove(member_service_id);
+				},
+				VerificationStatus::Unverified => {
+					VerificationStatuses::<T>::remove(member_service_id);
+					Self::issue_domain_challenge(member_service_id);
+				},
+			}
+			Self::deposit_event(Event::VerificationStatusSet { member_service_id, status });
+			Ok(())
+		}
+
+		/// Announce planned downtime of a member service. Health checks within the window are
+		/// recorded but don't count against the member service.
+		#[pallet::call_index(17)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn schedule_maintenance(
+			origin: OriginFor<T>,
+			member_service_id: u32,
+			start: T::BlockNumber,
+			end: T::BlockNumber,
+			reason: BoundedVec<u8, ConstU32<128>>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_member_service_owner(&sender, member_service_id)?;
+			ensure!(
+				start < end && end.saturating_sub(start) <= T::MaxMaintenanceDuration::get(),
+				Error::<T>::InvalidMaintenanceWindow,
+			);
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				start >= now.saturating_add(T::MaintenanceNotice::get()),
+				Error::<T>::MaintenanceNoticeTooShort,
+			);
+			let era = Self::era_of(start);
+			let count = MaintenanceCounts::<T>::get(era, member_service_id);
+			ensure!(count < T::MaxMaintenancesPerEra::get(), Error::<T>::TooManyMaintenances);
+			MaintenanceWindows::<T>::try_mutate(member_service_id, |windows| -> DispatchResult {
+				windows.retain(|window| window.end > now);
+				let window = MaintenanceWindow { start, end, reason: reason.clone() };
+				windows.try_push(window).map_err(|_| Error::<T>::TooManyMaintenances)?;
+				Ok(())
+			})?;
+			MaintenanceCounts::<T>::insert(era, member_service_id, count + 1);
+			Self::deposit_event(Event::MaintenanceScheduled {
+				member_service_id,
+				start,
+				end,
+				reason,
+			});
+			Ok(())
+		}
+
+		/// Attach a post-mortem, e.g. a URL or the hash of a document, to an incident of one of the
+		/// sender's member services.
+		#[pallet::call_index(18)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_post_mortem(
+			origin: OriginFor<T>,
+			incident_id: u32,
+			post_mortem: BoundedVec<u8, ConstU32<256>>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let mut incident =
+				Incidents::<T>::get(incident_id).ok_or(Error::<T>::IncidentNotFound)?;
+			Self::ensure_member_service_owner(&sender, incident.member_service_id)?;
+			incident.post_mortem = Some(post_mortem);
+			Incidents::<T>::insert(incident_id, incident);
+			Self::deposit_event(Event::PostMortemSet { incident_id });
+			Ok(())
+		}
+
+		/// Dispute failed health checks of one of the sender's member services, reserving
+		/// `DisputeBond`. The bond is refunded if the dispute isn't resolved within
+		/// `DisputeTimeout` blocks.
+		#[pallet::call_index(19)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn dispute_health_check(
+			origin: OriginFor<T>,
+			member_service_id: u32,
+			target: DisputeTarget<T::AccountId>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_member_service_owner(&sender, member_service_id)?;
+			let reports = Self::disputed_reports(member_service_id, &target)?;
+			ensure!(!reports.is_empty(), Error::<T>::NoReportsToDispute);
+			let id = DisputeCount::<T>::get().unwrap_or_default();
+			let next_id = id.checked_add(1).ok_or(Error::<T>::DisputeIdOverflow)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			let expires_at = now.saturating_add(T::DisputeTimeout::get().max(One::one()));
+			DisputeExpiries::<T>::try_mutate(expires_at, |ids| ids.try_push(id))
+				.map_err(|_| Error::<T>::TooManyDisputes)?;
+			DisputesByDisputant::<T>::try_mutate(&sender, |ids| ids.try_push(id))
+				.map_err(|_| Error::<T>::TooManyDisputes)?;
+			let bond = T::DisputeBond::get();
+			T::Currency::reserve(&sender, bond)?;
+			for (monitor, timestamp) in reports.iter() {
+				FailedReports::<T>::mutate(member_service_id, monitor, |failed_reports| {
+					failed_reports
+						.iter_mut()
+						.filter(|report| report.timestamp == *timestamp)
+						.for_each(|report| report.disputed = true);
+				});
+			}
+			DisputeCount::<T>::set(Some(next_id));
+			let count = reports.len() as u32;
+			let dispute = Dispute {
+				member_service_id,
+				disputant: sender.clone(),
+				target,
+				reports,
+				bond,
+				opened_at: now,
+				ayes: BoundedVec::default(),
+				nays: BoundedVec::default(),
+			};
+			Disputes::<T>::insert(id, dispute);
+			Self::deposit_event(Event::HealthCheckDisputed {
+				id,
+				member_service_id,
+				disputant: sender,
+				reports: count,
+			});
+			Ok(())
+		}
+
+		/// Uphold or reject a dispute.
+		#[pallet::call_index(20)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn resolve_dispute(origin: OriginFor<T>, id: u32, uphold: bool) -> DispatchResult {
+			T::SlashOrigin::ensure_origin(origin)?;
+			let dispute = Disputes::<T>::take(id).ok_or(Error::<T>::DisputeNotFound)?;
+			Self::resolve(id, dispute, uphold);
+			Ok(())
+		}
+
+		/// Cancel slashes deferred to `era`, by their indices in `UnappliedSlashes`.
+		#[pallet::call_index(22)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn cancel_deferred_slash(
+			origin: OriginFor<T>,
+			era: u32,
+			slash_indices: Vec<u32>,
+		) -> DispatchResult {
+			T::SlashOrigin::ensure_origin(origin)?;
+			ensure!(!slash_indices.is_empty(), Error::<T>::EmptyTargets);
+			let mut slash_indices = slash_indices;
+			slash_indices.sort_unstable();
+			slash_indices.dedup();
+			UnappliedSlashes::<T>::try_mutate(era, |slashes| -> DispatchResult {
+				let last = slash_indices.last().copied().unwrap_or_default();
+				ensure!((last as usize) < slashes.len(), Error::<T>::InvalidSlashIndex);
+				for index in slash_indices.into_iter().rev() {
+					let slash = slashes.remove(index as usize);
+					Self::deposit_event(Event::SlashCancelled {
+						era,
+						member_service_id: slash.member_service_id,
+						amount: slash.amount,
+					});
+				}
+				Ok(())
+			})
+		}
+
+		/// Lift the suspension of a member service and reset its breach streak.
+		#[pallet::call_index(23)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn resume_member_service(
+			origin: OriginFor<T>,
+			member_service_id: u32,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			SuspendedMemberServices::<T>::take(member_service_id)
+				.ok_or(Error::<T>::MemberServiceNotSuspended)?;
+			BreachStreaks::<T>::remove(member_service_id);
+			Self::deposit_event(Event::MemberServiceResumed { member_service_id });
+			Ok(())
+		}
+
+		/// Vote as a member on a dispute. It is resolved once `DisputeVotesRequired` members voted
+		/// for the same outcome.
+		#[pallet::call_index(21)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn vote_on_dispute(origin: OriginFor<T>, id: u32, uphold: bool) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let member = Members::<T>::get(&sender).ok_or(Error::<T>::MemberNotFound)?;
+			let mut dispute = Disputes::<T>::get(id).ok_or(Error::<T>::DisputeNotFound)?;
+			ensure!(dispute.disputant != sender, Error::<T>::OwnDispute);
+			ensure!(
+				!dispute.ayes.contains(&member.id) && !dispute.nays.contains(&member.id),
+				Error::<T>::AlreadyVoted,
+			);
+			let votes = if uphold { &mut dispute.ayes } else { &mut dispute.nays };
+			votes.try_push(member.id).map_err(|_| Error::<T>::TooManyVotes)?;
+			let resolved = votes.len() as u32 >= T::DisputeVotesRequired::get();
+			Self::deposit_event(Event::DisputeVoted { id, voter: sender, uphold });
+			if resolved {
+				Disputes::<T>::remove(id);
+				Self::resolve(id, dispute, uphold);
+			} else {
+				Disputes::<T>::insert(id, dispute);
+			}
+			Ok(())
+		}
+
+		/// Set the relative share of `MemberEraBudget` that member services of a service earn.
+		#[pallet::call_index(24)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_service_weight(
+			origin: OriginFor<T>,
+			service_id: u32,
+			weight: u32,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(Services::<T>::contains_key(service_id), Error::<T>::ServiceNotFound);
+			ServiceWeights::<T>::insert(service_id, weight);
+			Self::deposit_event(Event::ServiceWeightSet { service_id, weight });
+			Ok(())
+		}
+
+		/// Pay the sender's pending member payouts out of the slashes in the reward pot, and mint
+		/// the rest, as monitor rewards are.
+		#[pallet::call_index(25)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn claim_rewards(origin: OriginFor<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let amount = PendingRewards::<T>::get(&sender);
+			ensure!(!amount.is_zero(), Error::<T>::NoPendingRewards);
+			let pot = Self::reward_pot();
+			let from_pot = T::Currency::free_balance(&pot)
+				.saturating_sub(T::Currency::minimum_balance())
+				.min(amount);
+			if !from_pot.is_zero() {
+				T::Currency::transfer(&pot, &sender, from_pot, ExistenceRequirement::KeepAlive)?;
+			}
+			T::Currency::deposit_creating(&sender, amount.saturating_sub(from_pot));
+			PendingRewards::<T>::remove(&sender);
+			Self::deposit_event(Event::RewardsClaimed { member: sender, amount });
+			Ok(())
+		}
+
+		/// Dispatch `call` with the root origin, so that member proposals can make root-level
+		/// calls.
+		#[pallet::call_index(26)]
+		#[pallet::weight({
+			let dispatch_info = call.get_dispatch_info();
+			(
+				dispatch_info.weight.saturating_add(T::WeightInfo::dummy_weight()),
+				dispatch_info.class,
+			)
+		})]
+		pub fn dispatch_as_root(
+			origin: OriginFor<T>,
+			call: Box<<T as Config>::RuntimeCall>,
+		) -> DispatchResultWithPostInfo {
+			T::RootDispatchOrigin::ensure_origin(origin)?;
+			let result = call.dispatch_bypass_filter(frame_system::RawOrigin::Root.into());
+			Self::deposit_event(Event::RootDispatched {
+				result: result.map(|_| ()).map_err(|error| error.error),
+			});
+			Ok(Pays::No.into())
+		}
+
+		/// Apply for membership, reserving `ApplicationDeposit`. The application is decided on by
+		/// `AdminOrigin` or a vote of the members, and expires after `ApplicationTimeout` blocks.
+		#[pallet::call_index(27)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn apply_for_membership(
+			origin: OriginFor<T>,
+			name: BoundedVec<u8, ConstU32<64>>,
+			metadata: BoundedVec<u8, ConstU32<256>>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!Members::<T>::contains_key(&sender), Error::<T>::MemberAlreadyRegistered);
+			ensure!(!Applications::<T>::contains_key(&sender), Error::<T>::AlreadyApplied);
+			ensure!(!name.is_empty(), Error::<T>::InvalidMemberName);
+			let now = frame_system::Pallet::<T>::block_number();
+			let expires_at = now.saturating_add(T::ApplicationTimeout::get().max(One::one()));
+			ApplicationExpiries::<T>::try_mutate(expires_at, |applicants| {
+				applicants.try_push(sender.clone())
+			})
+			.map_err(|_| Error::<T>::TooManyApplications)?;
+			let deposit = T::ApplicationDeposit::get();
+			T::Currency::reserve(&sender, deposit)?;
+			Applications::<T>::insert(
+				&sender,
+				Application {
+					name: name.clone(),
+					metadata,
+					deposit,
+					expires_at,
+					ayes: BoundedVec::default(),
+					nays: BoundedVec::default(),
+				},
+			);
+			Self::deposit_event(Event::MembershipApplied { applicant: sender, name });
+			Ok(())
+		}
+
+		/// Admit `applicant` as a member.
+		#[pallet::call_index(28)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn approve_application(
+			origin: OriginFor<T>,
+			applicant: T::AccountId,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let application =
+				Applications::<T>::take(&applicant).ok_or(Error::<T>::ApplicationNotFound)?;
+			Self::ensure_identity(&applicant, T::RequireIdentityForApproval::get())?;
+			Self::ensure_room_for_member()?;
+			Self::decide(applicant, application, true);
+			Ok(())
+		}
+
+		/// Turn down the application of `applicant`.
+		#[pallet::call_index(29)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn reject_application(origin: OriginFor<T>, applicant: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let application =
+				Applications::<T>::take(&applicant).ok_or(Error::<T>::ApplicationNotFound)?;
+			Self::decide(applicant, application, false);
+			Ok(())
+		}
+
+		/// Vote as a member on an application. It is decided once `ApplicationVotesRequired`
+		/// members voted the same way.
+		#[pallet::call_index(30)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn vote_on_application(
+			origin: OriginFor<T>,
+			applicant: T::AccountId,
+			approve: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let member = Members::<T>::get(&sender).ok_or(Error::<T>::MemberNotFound)?;
+			let mut application =
+				Applications::<T>::get(&applicant).ok_or(Error::<T>::ApplicationNotFound)?;
+			ensure!(
+				!application.ayes.contains(&member.id) && !application.nays.contains(&member.id),
+				Error::<T>::AlreadyVoted,
+			);
+			let votes = if approve { &mut application.ayes } else { &mut application.nays };
+			votes.try_push(member.id).map_err(|_| Error::<T>::TooManyVotes)?;
+			let decided = votes.len() as u32 >= T::ApplicationVotesRequired::get();
+			Self::deposit_event(Event::ApplicationVoted {
+				applicant: applicant.clone(),
+				voter: sender,
+				approve,
+			});
+			if decided {
+				if approve {
+					Self::ensure_identity(&applicant, T::RequireIdentityForApproval::get())?;
+					Self::ensure_room_for_member()?;
+				}
+				Applications::<T>::remove(&applicant);
+				Self::decide(applicant, application, approve);
+			} else {
+				Applications::<T>::insert(&applicant, application);
+			}
+			Ok(())
+		}
+
+		/// Promote or demote a member to `level`.
+		#[pallet::call_index(31)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_member_level(
+			origin: OriginFor<T>,
+			member: T::AccountId,
+			level: u8,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(level <= T::MaxMemberLevel::get(), Error::<T>::InvalidLevel);
+			let current = Members::<T>::get(&member).ok_or(Error::<T>::MemberNotFound)?;
+			if level > current.level {
+				Self::ensure_identity(&member, T::RequireIdentityForPromotion::get())?;
+			}
+			Self::set_level(&member, level);
+			Ok(())
+		}
+
+		/// Set the lowest level a member must hold to register member services of a service.
+		/// Member services registered before keep running.
+		#[pallet::call_index(32)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_service_min_level(
+			origin: OriginFor<T>,
+			service_id: u32,
+			min_level: u8,
+		) -> DispatchResult {
+			T::RegistrarOrigin::ensure_origin(origin)?;
+			ensure!(min_level <= T::MaxMemberLevel::get(), Error::<T>::InvalidLevel);
+			Services::<T>::try_mutate(service_id, |service| -> DispatchResult {
+				let service = service.as_mut().ok_or(Error::<T>::ServiceNotFound)?;
+				service.min_level = min_level;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::ServiceMinLevelSet { service_id, min_level });
+			Ok(())
+		}
+
+		/// Propose to transfer the sender's membership to `new_account`, which must accept it with
+		/// `accept_membership_transfer`. Proposing again replaces the proposal.
+		#[pallet::call_index(33)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn transfer_membership(
+			origin: OriginFor<T>,
+			new_account: T::AccountId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(Members::<T>::contains_key(&sender), Error::<T>::MemberNotFound);
+			ensure!(sender != new_account, Error::<T>::TransferToSelf);
+			ensure!(!Members::<T>::contains_key(&new_account), Error::<T>::MemberAlreadyRegistered,);
+			PendingTransfers::<T>::insert(&sender, &new_account);
+			Self::deposit_event(Event::MembershipTransferProposed {
+				from: sender,
+				to: new_account,
+			});
+			Ok(())
+		}
+
+		/// Accept the transfer of the membership of `from` to the sender.
+		#[pallet::call_index(34)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn accept_membership_transfer(
+			origin: OriginFor<T>,
+			from: T::AccountId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(
+				PendingTransfers::<T>::get(&from).as_ref() == Some(&sender),
+				Error::<T>::TransferNotFound,
+			);
+			Self::do_transfer_membership(from, sender)
+		}
+
+		/// Move the membership of `from` to `to` without their consent, to recover a member whose
+		/// account is compromised.
+		#[pallet::call_index(35)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn force_transfer_membership(
+			origin: OriginFor<T>,
+			from: T::AccountId,
+			to: T::AccountId,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(from != to, Error::<T>::TransferToSelf);
+			Self::do_transfer_membership(from, to)
+		}
+
+		/// Rotate the x25519 public key that members seal their contacts to. Contacts sealed to
+		/// the previous key stay readable by its holders until their members update them.
+		#[pallet::call_index(36)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_contact_key(origin: OriginFor<T>, public: [u8; 32]) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			let key_id = CurrentContactKey::<T>::get().map_or(0, |key| key.id + 1);
+			CurrentContactKey::<T>::put(ContactKey { id: key_id, public });
+			Self::deposit_event(Event::ContactKeyRotated { key_id, public });
+			Ok(())
+		}
+
+		/// Store the sender's contact details, sealed to the contact key `key_id` with an x25519
+		/// sealed box.
+		#[pallet::call_index(37)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn set_contact(
+			origin: OriginFor<T>,
+			key_id: u32,
+			ciphertext: BoundedVec<u8, ConstU32<512>>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let member = Members::<T>::get(&sender).ok_or(Error::<T>::MemberNotFound)?;
+			let key = CurrentContactKey::<T>::get().ok_or(Error::<T>::NoContactKey)?;
+			ensure!(key_id == key.id, Error::<T>::StaleContactKey);
+			let version = Contacts::<T>::get(member.id).map_or(1, |contact| contact.version + 1);
+			Contacts::<T>::insert(member.id, EncryptedContact { key_id, version, ciphertext });
+			Self::deposit_event(Event::ContactUpdated { member_id: member.id, key_id, version });
+			Ok(())
+		}
+
+		/// Remove the sender's contact details.
+		#[pallet::call_index(38)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn remove_contact(origin: OriginFor<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let member = Members::<T>::get(&sender).ok_or(Error::<T>::MemberNotFound)?;
+			ensure!(Contacts::<T>::contains_key(member.id), Error::<T>::ContactNotFound);
+			Contacts::<T>::remove(member.id);
+			Self::deposit_event(Event::ContactRemoved { member_id: member.id });
+			Ok(())
+		}
+
+		/// Deregister one of the sender's member services and release what is left of its
+		/// deposit to the depositor. Member services with slashes pending can't be deregistered.
+		/// Their health checks and incidents are kept.
+		#[pallet::call_index(39)]
+		#[pallet::weight(T::WeightInfo::dummy_weight())]
+		pub fn deregister_member_service(
 			origin: OriginFor<T>,
 			member_service_id: u32,
-			timestamp: u64,
-			status: bool,
-			response_time_ms: u32,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			Self::ensure_member_service_owner(&sender, member_service_id)?;
+			// Slashes are deferred by `SlashDeferDuration` eras, which bounds the eras read.
+			let slash_pending = UnappliedSlashes::<T>::iter_values().any(|slashes| {
+				slashes.iter().any(|slash| slash.member_service_id == member_service_id)
+			});
+			ensure!(!slash_pending, Error::<T>::SlashPending);
+			if let Some(deposit) = MemberServiceDeposits::<T>::take(member_service_id) {
+				T::Currency::unreserve(&deposit.depositor, deposit.amount);
+				DepositsByDepositor::<T>::mutate(&deposit.depositor, |ids| {
+					ids.retain(|id| *id != member_service_id)
+				});
+			}
+			MemberServices::<T>::remove(member_service_id);
+			ActiveMemberServices::<T>::mutate(|active| active.saturating_dec());
+			VerificationStatuses::<T>::remove(member_service_id);
+			DomainChallenges::<T>::remove(member_service_id);
+			MaintenanceWindows::<T>::remove(member_service_id);
+			FailureStreaks::<T>::remove(member_service_id);
+			BreachStreaks::<T>::remove(member_service_id);
+			SuspendedMemberServices::<T>::remove(member_service_id);
+			ExpectedCertificates::<T>::remove(member_service_id);
+			CertificateStatuses::<T>::remove(member_service_id);
+			if let Some(id) = OpenIncidents::<T>::take(member_service_id) {
+				let now = frame_system::Pallet::<T>::block_number();
+				Incidents::<T>::mutate(id, |incident| {
+					if let Some(incident) = incident {
+						incident.ended_at = Some(now);
+					}
+				});
+			}
+			Self::deposit_event(Event::MemberServiceDeregistered { member_service_id });
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::submit_health_check_unsigned { payload, signature } = call {
+				let signature_valid =
+					SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone());
+				if !signature_valid {
+					return InvalidTransaction::BadProof.into()
+				}
+				Self::validate_payload_block(payload.block_number)?;
+				let interval = T::HealthCheckInterval::get().max(One::one());
+				let monitor =
+					Self::monitor_of_key(&payload.public).ok_or(InvalidTransaction::BadSigner)?;
+				let member_service_id = payload.health_check.member_service_id;
+				Self::validate_health_check(&monitor, member_service_id)
+					.map_err(health_check_invalidity::<T>)?;
+				ValidTransaction::with_tag_prefix("IbpHealthCheck")
+					.priority(T::UnsignedPriority::get())
+					.and_provides((monitor, member_service_id, payload.block_number / interval))
+					.longevity(interval.saturated_into::<u64>())
+					.propagate(true)
+					.build()
+			} else if let Call::submit_domain_attestation_unsigned { payload, signature } = call {
+				let signature_valid =
+					SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone());
+				if !signature_valid {
+					return InvalidTransaction::BadProof.into()
+				}
+				Self::validate_payload_block(payload.block_number)?;
+				let monitor =
+					Self::monitor_of_key(&payload.public).ok_or(InvalidTransaction::BadSigner)?;
+				let member_service_id = payload.member_service_id;
+				Self::validate_domain_attestation(&monitor, member_service_id, payload.token)
+					.map_err(|_| InvalidTransaction::Call)?;
+				ValidTransaction::with_tag_prefix("IbpDomainAttestation")
+					.priority(T::UnsignedPriority::get())
+					.and_provides((monitor, member_service_id))
+					.longevity(T::HealthCheckInterval::get().saturated_into::<u64>())
+					.propagate(true)
+					.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Rejects payloads signed for a future block or more than `HealthCheckInterval` blocks
+		/// ago.
+		fn validate_payload_block(block_number: T::BlockNumber) -> Result<(), InvalidTransaction> {
+			let now = frame_system::Pallet::<T>::block_number();
+			if block_number > now {
+				return Err(InvalidTransaction::Future)
+			}
+			if block_number.saturating_add(T::HealthCheckInterval::get().max(One::one())) <= now {
+				return Err(InvalidTransaction::Stale)
+			}
+			Ok(())
+		}
+
+		fn do_submit_health_check(
+			monitor: T::AccountId,
+			mut health_check: HealthCheck,
+		) -> DispatchResult {
+			let member_service_id = health_check.member_service_id;
+			Self::validate_health_check(&monitor, member_service_id)?;
 			let member_service = MemberServices::<T>::get(&member_service_id)
 				.ok_or(Error::<T>::MemberServiceNotFound)?;
-			let monitor_name = Monitors::<T>::get(&sender).ok_or(Error::<T>::MonitorNotFound)?;
+			let monitor_name = Monitors::<T>::get(&monitor).ok_or(Error::<T>::MonitorNotFound)?;
 			let mut service_health_checks =
-				match HealthChecks::<T>::get(&member_service_id, &sender) {
+				match HealthChecks::<T>::get(&member_service_id, &monitor) {
 					Some(service_health_checks) => service_health_checks,
 					None => BoundedVec::default(),
 				};
-			let health_check =
-				HealthCheck { member_service_id, timestamp, status, response_time_ms };
-			service_health_checks.try_push(health_check).unwrap();
-			HealthChecks::<T>::set(&member_service_id, &sender, Some(service_health_checks));
+			// Only JSON-RPC probes query the genesis hash.
+			let genesis_hash = Services::<T>::get(member_service.service_id)
+				.filter(|service| service.ty.is_rpc())
+				.and_then(|service| service.network_id)
+				.and_then(Networks::<T>::get)
+				.map(|network| network.genesis_hash);
+			health_check.normalize(genesis_hash);
+			Self::note_certificate(&monitor, &mut health_check);
+			Self::note_health_check(&member_service, &monitor, &health_check);
+			// Keep the most recent checks once the history is full.
+			if let Err(health_check) = service_health_checks.try_push(health_check) {
+				service_health_checks.remove(0);
+				let _ = service_health_checks.try_push(health_check);
+			}
+			HealthChecks::<T>::set(&member_service_id, &monitor, Some(service_health_checks));
+			LastHealthCheck::<T>::insert(
+				&member_service_id,
+				&monitor,
+				frame_system::Pallet::<T>::block_number(),
+			);
 			let reward: BalanceOf<T> = T::HealthCheckReward::get().saturated_into::<BalanceOf<T>>();
-			T::Currency::deposit_creating(&sender, reward);
+			T::Currency::deposit_creating(&monitor, reward);
 			Self::deposit_event(Event::HealthCheckSubmitted {
 				member_service_name: member_service.name.clone(),
 				monitor_name: monitor_name.clone(),
@@ -277,13 +2423,931 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::call_index(5)]
-		#[pallet::weight(T::WeightInfo::zero_weight())]
-		pub fn mint(origin: OriginFor<T>) -> DispatchResult {
-			let sender = ensure_signed(origin)?;
+		/// Weight of a health check, including the evaluation of the closed height window it may
+		/// trigger, which updates a rollup for each of up to `MAX_HEIGHT_REPORTS` member services.
+		pub fn health_check_weight() -> Weight {
+			T::WeightInfo::dummy_weight().saturating_add(
+				T::DbWeight::get()
+					.reads_writes(MAX_HEIGHT_REPORTS.into(), MAX_HEIGHT_REPORTS.into()),
+			)
+		}
+
+		/// Era that `block_number` belongs to.
+		pub fn era_of(block_number: T::BlockNumber) -> u32 {
+			(block_number / T::EraLength::get().max(One::one())).saturated_into()
+		}
+
+		/// Add a health check to the current era's rollup and its heights to the current window,
+		/// unless the member service is in maintenance.
+		fn note_health_check(
+			member_service: &MemberService,
+			monitor: &T::AccountId,
+			health_check: &HealthCheck,
+		) {
+			let now = frame_system::Pallet::<T>::block_number();
+			if Self::in_maintenance(member_service.id, now) {
+				return
+			}
+			Self::track_incident(member_service, monitor, health_check);
+			if !health_check.is_up() {
+				FailedReports::<T>::mutate(member_service.id, monitor, |reports| {
+					let report = FailedReport {
+						timestamp: health_check.timestamp,
+						block_number: now,
+						disputed: false,
+					};
+					// Keep the most recent reports once the bound is reached.
+					if let Err(report) = reports.try_push(report) {
+						reports.remove(0);
+						let _ = reports.try_push(report);
+					}
+				});
+			}
+			EraRollups::<T>::mutate(Self::era_of(now), member_service.id, |rollup| {
+				rollup.checks.saturating_inc();
+				if health_check.is_up() {
+					rollup.up_checks.saturating_inc();
+					rollup.total_response_time_ms = rollup
+						.total_response_time_ms
+						.saturating_add(health_check.response_time_ms.into());
+				}
+			});
+			let best_block = match (health_check.is_up(), health_check.best_block) {
+				(true, Some(best_block)) => best_block,
+				_ => return,
+			};
+			let window = now / T::HealthCheckInterval::get().max(One::one());
+			let mut height_window = match HeightWindows::<T>::get(member_service.service_id) {
+				Some(height_window) if height_window.window == window => height_window,
+				Some(height_window) => {
+					Self::evaluate_height_window(member_service.service_id, height_window);
+					HeightWindow { window, heights: BoundedVec::default() }
+				},
+				None => HeightWindow { window, heights: BoundedVec::default() },
+			};
+			match height_window
+				.heights
+				.iter_mut()
+				.find(|heights| heights.member_service_id == member_service.id)
+			{
+				Some(heights) => {
+					heights.best_block = heights.best_block.max(best_block);
+					heights.finalized_block =
+						heights.finalized_block.max(health_check.finalized_block);
+				},
+				None => {
+					// Heights beyond the bound are left out of the comparison.
+					let _ = height_window.heights.try_push(ReportedHeights {
+						member_service_id: member_service.id,
+						best_block,
+						finalized_block: health_check.finalized_block,
+					});
+				},
+			}
+			HeightWindows::<T>::insert(member_service.service_id, height_window);
+		}
+
+		/// Evaluate the height windows of all services that ended before the window of
+		/// `block_number`, so member services are compared even if nothing reports heights in the
+		/// next window. Bounded by the number of services.
+		fn close_height_windows(block_number: T::BlockNumber) -> Weight {
+			let window = block_number / T::HealthCheckInterval::get().max(One::one());
+			let height_windows = HeightWindows::<T>::iter().collect::<Vec<_>>();
+			let mut weight = T::DbWeight::get().reads(height_windows.len() as u64);
+			for (service_id, height_window) in height_windows {
+				if height_window.window >= window {
+					continue
+				}
+				let reports = height_window.heights.len() as u64;
+				HeightWindows::<T>::remove(service_id);
+				Self::evaluate_height_window(service_id, height_window);
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(reports, reports + 1));
+			}
+			weight
+		}
+
+		/// Compare the heights reported in a closed window with their medians, and record the lag
+		/// in the rollups of the era the window started in.
+		fn evaluate_height_window(service_id: u32, height_window: HeightWindow<T::BlockNumber>) {
+			let started = height_window
+				.window
+				.saturating_mul(T::HealthCheckInterval::get().max(One::one()));
+			let era = Self::era_of(started);
+			let best_median =
+				median(height_window.heights.iter().map(|heights| heights.best_block).collect());
+			let finalized_median = median(
+				height_window
+					.heights
+					.iter()
+					.filter_map(|heights| heights.finalized_block)
+					.collect(),
+			);
+			for heights in height_window.heights.iter() {
+				let best_lag = best_median.unwrap_or_default().saturating_sub(heights.best_block);
+				let finalized_lag = match (finalized_median, heights.finalized_block) {
+					(Some(median), Some(finalized_block)) => median.saturating_sub(finalized_block),
+					_ => 0,
+				};
+				let lagging =
+					best_lag > T::MaxBlockLag::get() || finalized_lag > T::MaxBlockLag::get();
+				EraRollups::<T>::mutate(era, heights.member_service_id, |rollup| {
+					rollup.height_windows.saturating_inc();
+					rollup.total_lag = rollup.total_lag.saturating_add(best_lag.into());
+					rollup.max_lag = rollup.max_lag.max(best_lag);
+					if lagging {
+						rollup.lagging_windows.saturating_inc();
+					}
+				});
+				if lagging {
+					Self::deposit_event(Event::ServiceLagging {
+						service_id,
+						member_service_id: heights.member_service_id,
+						best_lag,
+						finalized_lag,
+					});
+				}
+			}
+		}
+
+		/// Checks a reported certificate against the expected one and its expiry, and records it.
+		fn note_certificate(monitor: &T::AccountId, health_check: &mut HealthCheck) {
+			let report = match &health_check.certificate {
+				Some(report) => report.clone(),
+				None => return,
+			};
+			let member_service_id = health_check.member_service_id;
+			let previous = CertificateStatuses::<T>::get(member_service_id);
+			let mismatch =
+				ExpectedCertificates::<T>::get(member_service_id).map_or(false, |expected| {
+					expected
+						.fingerprint
+						.map_or(false, |fingerprint| fingerprint != report.fingerprint) ||
+						expected.issuer.map_or(false, |issuer| issuer != report.issuer)
+				});
+			if mismatch {
+				health_check.result = HealthCheckResult::Failed(FailureCode::CertificateMismatch);
+				let flagged = previous.as_ref().map_or(false, |status| {
+					status.mismatch && status.report.fingerprint == report.fingerprint
+				});
+				if !flagged {
+					Self::deposit_event(Event::SecurityIncident {
+						member_service_id,
+						monitor: monitor.clone(),
+						kind: SecurityIncidentKind::CertificateMismatch {
+							fingerprint: report.fingerprint,
+						},
+					});
+				}
+			} else if health_check.is_up() && report.expires_at <= health_check.timestamp {
+				health_check.result = HealthCheckResult::Failed(FailureCode::CertificateExpired);
+			}
+			let mut expiry_warned = previous.map_or(false, |status| {
+				status.expiry_warned && status.report.expires_at == report.expires_at
+			});
+			let warning =
+				u64::from(T::CertificateExpiryWarningDays::get()).saturating_mul(MILLISECS_PER_DAY);
+			if !expiry_warned && health_check.timestamp.saturating_add(warning) >= report.expires_at
+			{
+				Self::deposit_event(Event::CertificateExpiringSoon {
+					member_service_id,
+					expires_at: report.expires_at,
+				});
+				expiry_warned = true;
+			}
+			let status = CertificateStatus {
+				report,
+				reported_at: frame_system::Pallet::<T>::block_number(),
+				mismatch,
+				expiry_warned,
+			};
+			CertificateStatuses::<T>::insert(member_service_id, status);
+		}
+
+		/// Count a failed health check towards the member service's failure streak or open
+		/// incident, or close the incident on a successful one.
+		fn track_incident(
+			member_service: &MemberService,
+			monitor: &T::AccountId,
+			health_check: &HealthCheck,
+		) {
+			let now = frame_system::Pallet::<T>::block_number();
+			let code = match health_check.result {
+				HealthCheckResult::Ok => {
+					FailureStreaks::<T>::remove(member_service.id);
+					if let Some(id) = OpenIncidents::<T>::take(member_service.id) {
+						if let Some(mut incident) = Incidents::<T>::get(id) {
+							incident.ended_at = Some(now);
+							Self::deposit_event(Event::IncidentClosed {
+								id,
+								member_service_id: member_service.id,
+								started_at: incident.streak.started_at,
+								ended_at: now,
+							});
+							// Incidents that fell out of the history while open go once closed.
+							let retained = IncidentCount::<T>::get()
+								.unwrap_or_default()
+								.saturating_sub(T::MaxIncidentHistory::get());
+							if id < retained {
+								Incidents::<T>::remove(id);
+							} else {
+								Incidents::<T>::insert(id, incident);
+							}
+						}
+					}
+					return
+				},
+				HealthCheckResult::Failed(code) => code,
+			};
+			if let Some(id) = OpenIncidents::<T>::get(member_service.id) {
+				Incidents::<T>::mutate(id, |incident| {
+					if let Some(incident) = incident {
+						incident.streak.note_failure(monitor.clone(), code);
+					}
+				});
+				return
+			}
+			let mut streak = FailureStreaks::<T>::get(member_service.id).unwrap_or(FailureStreak {
+				failures: 0,
+				started_at: now,
+				monitors: BoundedVec::default(),
+				peak_failure: code,
+			});
+			streak.note_failure(monitor.clone(), code);
+			if streak.failures < T::IncidentThreshold::get() {
+				FailureStreaks::<T>::insert(member_service.id, streak);
+				return
+			}
+			FailureStreaks::<T>::remove(member_service.id);
+			let id = IncidentCount::<T>::get().unwrap_or_default();
+			IncidentCount::<T>::set(Some(id.saturating_add(1)));
+			if let Some(expired) = id.checked_sub(T::MaxIncidentHistory::get()) {
+				if Incidents::<T>::get(expired)
+					.map_or(false, |incident| incident.ended_at.is_some())
+				{
+					Incidents::<T>::remove(expired);
+				}
+			}
+			let peak_failure = streak.peak_failure;
+			let incident = Incident {
+				id,
+				service_id: member_service.service_id,
+				member_service_id: member_service.id,
+				streak,
+				ended_at: None,
+				post_mortem: None,
+			};
+			Incidents::<T>::insert(id, incident);
+			OpenIncidents::<T>::insert(member_service.id, id);
+			Self::deposit_event(Event::IncidentOpened {
+				id,
+				member_service_id: member_service.id,
+				peak_failure,
+			});
+		}
+
+		/// Monitors and timestamps of the undisputed failed reports `target` refers to.
+		fn disputed_reports(
+			member_service_id: u32,
+			target: &DisputeTarget<T::AccountId>,
+		) -> Result<BoundedVec<(T::AccountId, u64), ConstU32<64>>, DispatchError> {
+			let mut reports = BoundedVec::<_, ConstU32<64>>::default();
+			match target {
+				DisputeTarget::Reports { monitor, timestamps } => {
+					let failed_reports = FailedReports::<T>::get(member_service_id, monitor);
+					for timestamp in timestamps.iter() {
+						let report = failed_reports
+							.iter()
+							.find(|report| report.timestamp == *timestamp)
+							.ok_or(Error::<T>::ReportNotFound)?;
+						ensure!(!report.disputed, Error::<T>::ReportAlreadyDisputed);
+						let _ = reports.try_push((monitor.clone(), *timestamp));
+					}
+				},
+				DisputeTarget::Incident(incident_id) => {
+					let incident =
+						Incidents::<T>::get(incident_id).ok_or(Error::<T>::IncidentNotFound)?;
+					ensure!(
+						incident.member_service_id == member_service_id,
+						Error::<T>::IncidentNotFound
+					);
+					let ended_at =
+						incident.ended_at.unwrap_or_else(frame_system::Pallet::<T>::block_number);
+					for monitor in incident.streak.monitors.iter() {
+						let failed_reports = FailedReports::<T>::get(member_service_id, monitor);
+						let incident_reports = failed_reports.iter().filter(|report| {
+							!report.disputed &&
+								report.block_number >= incident.streak.started_at &&
+								report.block_number <= ended_at
+						});
+						for report in incident_reports {
+							// Reports beyond the bound stay undisputed.
+							let _ = reports.try_push((monitor.clone(), report.timestamp));
+						}
+					}
+				},
+			}
+			Ok(reports)
+		}
+
+		/// Drop a dispute that was taken from storage from the open disputes of its disputant.
+		fn forget_dispute(disputant: &T::AccountId, id: u32) {
+			DisputesByDisputant::<T>::mutate_exists(disputant, |ids| {
+				if let Some(open) = ids {
+					open.retain(|open_id| *open_id != id);
+					if open.is_empty() {
+						*ids = None;
+					}
+				}
+			});
+		}
+
+		/// Apply the outcome of a dispute that was taken from storage.
+		fn resolve(
+			id: u32,
+			dispute: Dispute<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+			upheld: bool,
+		) {
+			Self::forget_dispute(&dispute.disputant, id);
+			if !upheld {
+				let (slashed, _) = T::Currency::slash_reserved(&dispute.disputant, dispute.bond);
+				T::Currency::resolve_creating(&Self::reward_pot(), slashed);
+				Self::deposit_event(Event::DisputeResolved { id, upheld });
+				return
+			}
+			T::Currency::unreserve(&dispute.disputant, dispute.bond);
 			let reward: BalanceOf<T> = T::HealthCheckReward::get().saturated_into::<BalanceOf<T>>();
-			T::Currency::deposit_creating(&sender, reward);
+			let member_service_id = dispute.member_service_id;
+			for (monitor, timestamp) in dispute.reports.iter() {
+				let report = FailedReports::<T>::mutate(member_service_id, monitor, |reports| {
+					let index = reports.iter().position(|report| report.timestamp == *timestamp)?;
+					Some(reports.remove(index))
+				});
+				let report = match report {
+					Some(report) => report,
+					None => continue,
+				};
+				let era = Self::era_of(report.block_number);
+				if EraRollups::<T>::contains_key(era, member_service_id) {
+					EraRollups::<T>::mutate(era, member_service_id, |rollup| {
+						rollup.checks.saturating_dec();
+					});
+				}
+				let (reversed, _) = T::Currency::slash(monitor, reward);
+				T::Currency::resolve_creating(&Self::reward_pot(), reversed);
+				MonitorStrikes::<T>::mutate(monitor, |strikes| strikes.saturating_inc());
+			}
+			Self::deposit_event(Event::DisputeResolved { id, upheld });
+		}
+
+		/// Refund the deposit of an application taken out of `Applications`, and admit the
+		/// applicant as a member if `approved`.
+		fn decide(
+			applicant: T::AccountId,
+			application: Application<BalanceOf<T>, T::BlockNumber>,
+			approved: bool,
+		) {
+			T::Currency::unreserve(&applicant, application.deposit);
+			if !approved {
+				Self::deposit_event(Event::ApplicationRejected { applicant });
+				return
+			}
+			let id = MemberCount::<T>::get().unwrap_or_default();
+			MemberCount::<T>::set(Some(id + 1));
+			let name = application.name;
+			Members::<T>::insert(&applicant, Member { id, name: name.clone(), level: 0 });
+			Self::members_changed(&[applicant.clone()], &[]);
+			Self::deposit_event(Event::ApplicationApproved { applicant: applicant.clone() });
+			Self::deposit_event(Event::MemberRegistered { account_id: applicant, id, name });
+		}
+
+		/// Checks that another member can be admitted. Members are never removed, so their count is
+		/// `MemberCount`.
+		fn ensure_room_for_member() -> DispatchResult {
+			let members = MemberCount::<T>::get().unwrap_or_default();
+			ensure!(members < T::MaxMembers::get(), Error::<T>::TooManyMembers);
+			Ok(())
+		}
+
+		/// Drop the applications that expire at `block_number` and refund their deposits.
+		fn expire_applications(block_number: T::BlockNumber) -> Weight {
+			let applicants = ApplicationExpiries::<T>::take(block_number);
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			for applicant in applicants {
+				weight.saturating_accrue(T::DbWeight::get().reads(1));
+				// The applicant may have been decided on, and applied again since.
+				let application = match Applications::<T>::get(&applicant) {
+					Some(application) if application.expires_at == block_number => application,
+					_ => continue,
+				};
+				Applications::<T>::remove(&applicant);
+				T::Currency::unreserve(&applicant, application.deposit);
+				weight.saturating_accrue(T::DbWeight::get().writes(2));
+				Self::deposit_event(Event::ApplicationExpired { applicant });
+			}
+			weight
+		}
+
+		/// Refund the bonds of the disputes due at this block that weren't resolved.
+		fn expire_disputes(block_number: T::BlockNumber) -> Weight {
+			let ids = DisputeExpiries::<T>::take(block_number);
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			for id in ids {
+				weight.saturating_accrue(T::DbWeight::get().reads(1));
+				let dispute = match Disputes::<T>::take(id) {
+					Some(dispute) => dispute,
+					None => continue,
+				};
+				T::Currency::unreserve(&dispute.disputant, dispute.bond);
+				Self::forget_dispute(&dispute.disputant, id);
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(1, 3));
+				Self::deposit_event(Event::DisputeExpired { id });
+			}
+			weight
+		}
+
+		/// Move the membership of `from` to `to`, along with the deposits, open disputes and
+		/// monitors of `from`, its pending rewards and its SLA streak. Member services and votes
+		/// follow the member id.
+		fn do_transfer_membership(from: T::AccountId, to: T::AccountId) -> DispatchResult {
+			let member = Members::<T>::take(&from).ok_or(Error::<T>::MemberNotFound)?;
+			ensure!(!Members::<T>::contains_key(&to), Error::<T>::MemberAlreadyRegistered);
+			ensure!(!Applications::<T>::contains_key(&to), Error::<T>::AlreadyApplied);
+			PendingTransfers::<T>::remove(&from);
+			Members::<T>::insert(&to, member);
+			// `to` isn't a member, so it holds no deposits, disputes or monitors to merge with.
+			let deposits = DepositsByDepositor::<T>::take(&from);
+			for member_service_id in deposits.iter().copied() {
+				let mut deposit = match MemberServiceDeposits::<T>::get(member_service_id) {
+					Some(deposit) => deposit,
+					None => continue,
+				};
+				Self::repatriate_reserve(&from, &to, deposit.amount)?;
+				deposit.depositor = to.clone();
+				MemberServiceDeposits::<T>::insert(member_service_id, deposit);
+			}
+			if !deposits.is_empty() {
+				DepositsByDepositor::<T>::insert(&to, deposits);
+			}
+			let disputes = DisputesByDisputant::<T>::take(&from);
+			for id in disputes.iter().copied() {
+				let mut dispute = match Disputes::<T>::get(id) {
+					Some(dispute) => dispute,
+					None => continue,
+				};
+				Self::repatriate_reserve(&from, &to, dispute.bond)?;
+				dispute.disputant = to.clone();
+				Disputes::<T>::insert(id, dispute);
+			}
+			if !disputes.is_empty() {
+				DisputesByDisputant::<T>::insert(&to, disputes);
+			}
+			let monitors = MonitorsByOwner::<T>::take(&from);
+			for monitor in monitors.iter() {
+				MonitorOwners::<T>::insert(monitor, &to);
+			}
+			if !monitors.is_empty() {
+				MonitorsByOwner::<T>::insert(&to, monitors);
+			}
+			let pending = PendingRewards::<T>::take(&from);
+			if !pending.is_zero() {
+				PendingRewards::<T>::insert(&to, pending);
+			}
+			SlaStreaks::<T>::insert(&to, SlaStreaks::<T>::take(&from));
+			Self::members_changed(&[to.clone()], &[from.clone()]);
+			Self::deposit_event(Event::MembershipTransferred { from, to });
+			Ok(())
+		}
+
+		/// Move `amount` of the reserved balance of `from` to the reserve of `to`, failing unless
+		/// all of it is moved.
+		fn repatriate_reserve(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let remainder =
+				T::Currency::repatriate_reserved(from, to, amount, BalanceStatus::Reserved)?;
+			ensure!(remainder.is_zero(), Error::<T>::ReserveTooLow);
+			Ok(())
+		}
+
+		/// Notify `MembersChanged` that `incoming` joined and `outgoing` left the members.
+		fn members_changed(incoming: &[T::AccountId], outgoing: &[T::AccountId]) {
+			let mut members: Vec<T::AccountId> = Members::<T>::iter_keys().collect();
+			members.sort();
+			let mut incoming = incoming.to_vec();
+			incoming.sort();
+			let mut outgoing = outgoing.to_vec();
+			outgoing.sort();
+			T::MembersChanged::change_members_sorted(&incoming, &outgoing, &members);
+		}
+
+		/// Account that slashes are paid into.
+		pub fn reward_pot() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// Credit the members of the verified, unsuspended member services checked in `era` with
+		/// their share of `MemberEraBudget`, scaled by their uptime, which lagging windows reduce,
+		/// and latency score. Shares are in proportion to the service weight times one more than
+		/// the member's level.
+		fn pay_members(era: u32) -> Weight {
+			let mut weight = Weight::zero();
+			let members: BTreeMap<u32, (T::AccountId, u8)> = Members::<T>::iter()
+				.map(|(account, member)| (member.id, (account, member.level)))
+				.collect();
+			let mut earners = Vec::new();
+			for (member_service_id, member_service) in MemberServices::<T>::iter() {
+				weight.saturating_accrue(T::DbWeight::get().reads(3));
+				if !Self::is_verified(member_service_id) ||
+					SuspendedMemberServices::<T>::contains_key(member_service_id)
+				{
+					continue
+				}
+				let rollup = EraRollups::<T>::get(era, member_service_id);
+				if rollup.checks == 0 {
+					continue
+				}
+				let (member, level) = match members.get(&member_service.member_id) {
+					Some(member) => member.clone(),
+					None => continue,
+				};
+				let service_weight = ServiceWeights::<T>::get(member_service.service_id);
+				earners.push((member_service, rollup, member, level, service_weight));
+			}
+			let earner_weight = |level: u8, service_weight: u32| {
+				service_weight.saturating_mul(u32::from(level) + 1)
+			};
+			let total_weight = earners.iter().fold(0u32, |total, (_, _, _, level, weight)| {
+				total.saturating_add(earner_weight(*level, *weight))
+			});
+			if total_weight == 0 {
+				return weight
+			}
+			let budget = T::MemberEraBudget::get();
+			for (member_service, rollup, member, level, service_weight) in earners {
+				let uptime = rollup.uptime();
+				let latency_score = match rollup.up_checks {
+					0 => Perbill::zero(),
+					up_checks => {
+						let mean = rollup.total_response_time_ms / u64::from(up_checks);
+						let target = u64::from(T::TargetResponseTime::get());
+						if mean <= target {
+							Perbill::one()
+						} else {
+							Perbill::from_rational(target, mean)
+						}
+					},
+				};
+				let share =
+					Perbill::from_rational(earner_weight(level, service_weight), total_weight) *
+						budget;
+				let amount = uptime * (latency_score * share);
+				if amount.is_zero() {
+					continue
+				}
+				PendingRewards::<T>::mutate(&member, |pending| pending.saturating_accrue(amount));
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(1, 1));
+				Self::deposit_event(Event::MemberPaid {
+					member,
+					member_service_id: member_service.id,
+					era,
+					uptime,
+					latency_score,
+					service_weight,
+					level,
+					amount,
+				});
+			}
+			weight
+		}
+
+		/// Count the eras in a row each member met or missed the SLO, with the verified,
+		/// unsuspended member services checked in `era`, and promote or demote members whose streak
+		/// reached `PromotionEras` or `DemotionEras`.
+		fn review_levels(era: u32) -> Weight {
+			let mut weight = Weight::zero();
+			let mut met_slo: BTreeMap<u32, bool> = BTreeMap::new();
+			for (member_service_id, member_service) in MemberServices::<T>::iter() {
+				weight.saturating_accrue(T::DbWeight::get().reads(3));
+				if !Self::is_verified(member_service_id) ||
+					SuspendedMemberServices::<T>::contains_key(member_service_id)
+				{
+					continue
+				}
+				let rollup = EraRollups::<T>::get(era, member_service_id);
+				if rollup.checks == 0 {
+					continue
+				}
+				let met = rollup.uptime() >= T::SloUptime::get();
+				met_slo
+					.entry(member_service.member_id)
+					.and_modify(|all| *all &= met)
+					.or_insert(met);
+			}
+			let members: Vec<(T::AccountId, Member)> = Members::<T>::iter().collect();
+			for (account, member) in members {
+				weight.saturating_accrue(T::DbWeight::get().reads_writes(2, 1));
+				// Members with nothing checked keep their streaks.
+				let met = match met_slo.get(&member.id) {
+					Some(met) => *met,
+					None => continue,
+				};
+				let mut streak = SlaStreaks::<T>::get(&account);
+				if met {
+					streak.met.saturating_inc();
+					streak.missed = 0;
+				} else {
+					streak.missed.saturating_inc();
+					streak.met = 0;
+				}
+				let promotion_eras = T::PromotionEras::get();
+				let demotion_eras = T::DemotionEras::get();
+				// Members without a required identity keep their streak until they verify it.
+				if promotion_eras > 0 &&
+					streak.met >= promotion_eras &&
+					member.level < T::MaxMemberLevel::get() &&
+					Self::ensure_identity(&account, T::RequireIdentityForPromotion::get())
+						.is_ok()
+				{
+					Self::set_level(&account, member.level + 1);
+				} else if demotion_eras > 0 && streak.missed >= demotion_eras && member.level > 0 {
+					Self::set_level(&account, member.level - 1);
+				} else {
+					SlaStreaks::<T>::insert(&account, streak);
+				}
+			}
+			weight
+		}
+
+		/// Set the level of a member, resetting its SLA streak.
+		fn set_level(account: &T::AccountId, level: u8) {
+			let previous = Members::<T>::mutate(account, |member| {
+				member.as_mut().map(|member| core::mem::replace(&mut member.level, level))
+			});
+			SlaStreaks::<T>::remove(account);
+			match previous {
+				Some(previous) if level > previous =>
+					Self::deposit_event(Event::MemberPromoted { member: account.clone(), level }),
+				Some(previous) if level < previous =>
+					Self::deposit_event(Event::MemberDemoted { member: account.clone(), level }),
+				_ => (),
+			}
+		}
+
+		/// Count the SLO breaches of the era that ended and warn, defer slashes to be applied
+		/// `SlashDeferDuration` eras after `era`, or suspend member services according to the
+		/// number of breaches in a row.
+		fn penalize_breaches(ended: u32, era: u32) -> Weight {
+			let mut weight = Weight::zero();
+			for member_service_id in MemberServices::<T>::iter_keys() {
+				weight.saturating_accrue(T::DbWeight::get().reads(3));
+				if !Self::is_verified(member_service_id) ||
+					SuspendedMemberServices::<T>::contains_key(member_service_id)
+				{
+					continue
+				}
+				let rollup = EraRollups::<T>::get(ended, member_service_id);
+				let uptime = rollup.uptime();
+				if rollup.checks == 0 || uptime >= T::SloUptime::get() {
+					BreachStreaks::<T>::remove(member_service_id);
+					continue
+				}
+				let consecutive = BreachStreaks::<T>::get(member_service_id).saturating_add(1);
+				BreachStreaks::<T>::insert(member_service_id, consecutive);
+				weight.saturating_accrue(T::DbWeight::get().writes(2));
+				Self::deposit_event(Event::SloBreached {
+					member_service_id,
+					era: ended,
+					uptime,
+					consecutive,
+				});
+				if consecutive >= T::SlashAfterBreaches::get() {
+					Self::defer_slash(member_service_id, era);
+				}
+				if consecutive >= T::SuspendAfterBreaches::get() {
+					let now = frame_system::Pallet::<T>::block_number();
+					SuspendedMemberServices::<T>::insert(member_service_id, now);
+					Self::deposit_event(Event::MemberServiceSuspended { member_service_id });
+				}
+			}
+			weight
+		}
+
+		/// Schedule a `BreachSlash` slash of a member service's deposit.
+		fn defer_slash(member_service_id: u32, era: u32) {
+			let deposit = match MemberServiceDeposits::<T>::get(member_service_id) {
+				Some(deposit) => deposit,
+				None => return,
+			};
+			let amount = T::BreachSlash::get() * deposit.amount;
+			if amount.is_zero() {
+				return
+			}
+			let apply_era = era.saturating_add(T::SlashDeferDuration::get());
+			let pushed = UnappliedSlashes::<T>::mutate(apply_era, |slashes| {
+				slashes.try_push(UnappliedSlash { member_service_id, amount })
+			});
+			match pushed {
+				Ok(()) => Self::deposit_event(Event::SlashDeferred {
+					member_service_id,
+					amount,
+					apply_era,
+				}),
+				Err(_) => log::warn!(
+					target: "runtime::ibp",
+					"Too many slashes deferred to era {}, dropped slash of member service {}",
+					apply_era,
+					member_service_id,
+				),
+			}
+		}
+
+		/// Slash the deposits of the slashes deferred to `era` into the reward pot.
+		fn apply_slashes(era: u32) -> Weight {
+			let slashes = UnappliedSlashes::<T>::take(era);
+			let weight = T::DbWeight::get()
+				.reads_writes(1 + 2 * slashes.len() as u64, 1 + 3 * slashes.len() as u64);
+			for slash in slashes {
+				let member_service_id = slash.member_service_id;
+				let mut deposit = match MemberServiceDeposits::<T>::get(member_service_id) {
+					Some(deposit) => deposit,
+					None => continue,
+				};
+				let amount = slash.amount.min(deposit.amount);
+				let (slashed, _) = T::Currency::slash_reserved(&deposit.depositor, amount);
+				T::Currency::resolve_creating(&Self::reward_pot(), slashed);
+				deposit.amount = deposit.amount.saturating_sub(amount);
+				MemberServiceDeposits::<T>::insert(member_service_id, deposit);
+				Self::deposit_event(Event::SlashApplied { member_service_id, amount });
+			}
+			weight
+		}
+
+		/// Whether a member service is in a maintenance window at `block_number`.
+		pub fn in_maintenance(member_service_id: u32, block_number: T::BlockNumber) -> bool {
+			MaintenanceWindows::<T>::get(member_service_id)
+				.iter()
+				.any(|window| window.contains(&block_number))
+		}
+
+		/// Registry data and verified identity of a member, for the runtime API.
+		pub fn member_details(member_id: u32) -> Option<MemberDetails> {
+			Members::<T>::iter().find(|(_, member)| member.id == member_id).map(
+				|(account, member)| MemberDetails {
+					id: member.id,
+					name: member.name.into_inner(),
+					level: member.level,
+					identity: T::IdentityVerifier::verified_identity(&account),
+				},
+			)
+		}
+
+		/// Checks that `who` has a verified identity, if `required`.
+		fn ensure_identity(who: &T::AccountId, required: bool) -> DispatchResult {
+			ensure!(
+				!required || T::IdentityVerifier::verified_identity(who).is_some(),
+				Error::<T>::IdentityNotVerified,
+			);
 			Ok(())
 		}
+
+		/// Current and upcoming maintenance windows of a member service, for the runtime API.
+		pub fn maintenance_windows(
+			member_service_id: u32,
+		) -> Vec<MaintenanceWindow<T::BlockNumber>> {
+			let now = frame_system::Pallet::<T>::block_number();
+			MaintenanceWindows::<T>::get(member_service_id)
+				.into_iter()
+				.filter(|window| window.end > now)
+				.collect()
+		}
+
+		/// Latest certificate reported for a member service, for the runtime API.
+		pub fn certificate_status(
+			member_service_id: u32,
+		) -> Option<CertificateStatus<T::BlockNumber>> {
+			CertificateStatuses::<T>::get(member_service_id)
+		}
+
+		/// Checks that `who` is the member that registered `member_service_id`.
+		fn ensure_member_service_owner(
+			who: &T::AccountId,
+			member_service_id: u32,
+		) -> Result<MemberService, DispatchError> {
+			let member = Members::<T>::get(who).ok_or(Error::<T>::MemberNotFound)?;
+			let member_service = MemberServices::<T>::get(member_service_id)
+				.ok_or(Error::<T>::MemberServiceNotFound)?;
+			ensure!(member_service.member_id == member.id, Error::<T>::NotMemberServiceOwner);
+			Ok(member_service)
+		}
+
+		/// Issue a new domain challenge for a member service, replacing any open one. The token is
+		/// derived from the parent block hash, so it can't be known before the member service is
+		/// registered.
+		fn issue_domain_challenge(member_service_id: u32) {
+			let parent_hash = frame_system::Pallet::<T>::parent_hash();
+			let token = H256(sp_io::hashing::blake2_256(
+				&(b"ibp/domain-challenge", member_service_id, parent_hash).encode(),
+			));
+			let challenge = DomainChallenge {
+				token,
+				issued_at: frame_system::Pallet::<T>::block_number(),
+				attestations: BoundedVec::default(),
+			};
+			DomainChallenges::<T>::insert(member_service_id, challenge);
+			Self::deposit_event(Event::DomainChallengeIssued { member_service_id, token });
+		}
+
+		fn do_attest_domain(
+			monitor: T::AccountId,
+			member_service_id: u32,
+			token: H256,
+		) -> DispatchResult {
+			Self::validate_domain_attestation(&monitor, member_service_id, token)?;
+			let mut challenge = DomainChallenges::<T>::get(member_service_id)
+				.ok_or(Error::<T>::MemberServiceAlreadyVerified)?;
+			challenge
+				.attestations
+				.try_push(monitor.clone())
+				.map_err(|_| Error::<T>::TooManyAttestations)?;
+			Self::deposit_event(Event::DomainAttested { member_service_id, monitor });
+			if challenge.attestations.len() as u32 >= T::RequiredAttestations::get() {
+				DomainChallenges::<T>::remove(member_service_id);
+				let status = VerificationStatus::Verified;
+				VerificationStatuses::<T>::insert(member_service_id, status);
+				Self::deposit_event(Event::VerificationStatusSet { member_service_id, status });
+			} else {
+				DomainChallenges::<T>::insert(member_service_id, challenge);
+			}
+			Ok(())
+		}
+
+		/// Checks that `monitor` may attest that `token` is published for `member_service_id`.
+		pub fn validate_domain_attestation(
+			monitor: &T::AccountId,
+			member_service_id: u32,
+			token: H256,
+		) -> Result<(), Error<T>> {
+			ensure!(Monitors::<T>::contains_key(monitor), Error::<T>::MonitorNotFound);
+			let member_service = MemberServices::<T>::get(member_service_id)
+				.ok_or(Error::<T>::MemberServiceNotFound)?;
+			let challenge = DomainChallenges::<T>::get(member_service_id)
+				.ok_or(Error::<T>::MemberServiceAlreadyVerified)?;
+			ensure!(challenge.token == token, Error::<T>::InvalidChallengeToken);
+			ensure!(!challenge.attestations.contains(monitor), Error::<T>::AlreadyAttested);
+			let owner = MonitorOwners::<T>::get(monitor).and_then(Members::<T>::get);
+			ensure!(
+				owner.map_or(true, |owner| owner.id != member_service.member_id),
+				Error::<T>::SelfAttestation,
+			);
+			Ok(())
+		}
+
+		/// Whether monitors confirmed that the member of a member service controls its address.
+		pub fn is_verified(member_service_id: u32) -> bool {
+			VerificationStatuses::<T>::get(member_service_id) == VerificationStatus::Verified
+		}
+
+		/// Monitor that `key` is bound to, if any.
+		pub fn monitor_of_key(key: &T::Public) -> Option<T::AccountId> {
+			MonitorKeys::<T>::get(key.clone().into_account())
+		}
+
+		/// Checks that `monitor` may submit a health check for `member_service_id` at the current
+		/// block. Shared by the dispatchables, [`CheckHealthCheck`] and `ValidateUnsigned`.
+		pub fn validate_health_check(
+			monitor: &T::AccountId,
+			member_service_id: u32,
+		) -> Result<(), Error<T>> {
+			ensure!(Monitors::<T>::contains_key(monitor), Error::<T>::MonitorNotFound);
+			ensure!(
+				MemberServices::<T>::contains_key(member_service_id),
+				Error::<T>::MemberServiceNotFound,
+			);
+			ensure!(Self::is_verified(member_service_id), Error::<T>::MemberServiceNotVerified);
+			ensure!(
+				!SuspendedMemberServices::<T>::contains_key(member_service_id),
+				Error::<T>::MemberServiceSuspended,
+			);
+			if let Some(last) = LastHealthCheck::<T>::get(member_service_id, monitor) {
+				let now = frame_system::Pallet::<T>::block_number();
+				ensure!(
+					now >= last.saturating_add(T::HealthCheckInterval::get()),
+					Error::<T>::HealthCheckTooFrequent,
+				);
+			}
+			Ok(())
+		}
+	}
+
+	/// Upper median of `values`.
+	fn median(mut values: Vec<u32>) -> Option<u32> {
+		values.sort_unstable();
+		values.get(values.len() / 2).copied()
+	}
+
+	impl<T: Config> BoundToRuntimeAppPublic for Pallet<T> {
+		type Public = crypto::Public;
 	}
 }
@@ -0,0 +1,842 @@
+//! Storage migrations of pallet-ibp.
+
+use super::*;
+use frame_support::{sp_std::vec::Vec, traits::OnRuntimeUpgrade};
+
+const LOG_TARGET: &str = "runtime::ibp";
+
+/// Replaces `HealthCheck::status` with a `HealthCheckResult`.
+pub mod v1 {
+	use super::*;
+
+	#[derive(Decode)]
+	struct OldHealthCheck {
+		member_service_id: u32,
+		timestamp: u64,
+		status: bool,
+		response_time_ms: u32,
+	}
+
+	/// `HealthChecks` as of storage version 1.
+	#[frame_support::storage_alias]
+	pub(super) type HealthChecks<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		u32,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		BoundedVec<HealthCheckV1, ConstU32<512>>,
+	>;
+
+	/// `HealthCheck` as of storage version 1.
+	#[derive(Encode, Decode)]
+	pub(super) struct HealthCheckV1 {
+		pub member_service_id: u32,
+		pub timestamp: u64,
+		pub result: HealthCheckResult,
+		pub response_time_ms: u32,
+		pub best_block: Option<u32>,
+		pub finalized_block: Option<u32>,
+		pub peer_count: Option<u32>,
+		pub is_syncing: Option<bool>,
+	}
+
+	impl From<OldHealthCheck> for HealthCheckV1 {
+		fn from(old: OldHealthCheck) -> Self {
+			HealthCheckV1 {
+				member_service_id: old.member_service_id,
+				timestamp: old.timestamp,
+				result: if old.status {
+					HealthCheckResult::Ok
+				} else {
+					HealthCheckResult::Failed(FailureCode::Unknown)
+				},
+				response_time_ms: old.response_time_ms,
+				best_block: None,
+				finalized_block: None,
+				peer_count: None,
+				is_syncing: None,
+			}
+		}
+	}
+
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 0 {
+				log::info!(target: LOG_TARGET, "v1 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut translated = 0u64;
+			HealthChecks::<T>::translate::<BoundedVec<OldHealthCheck, ConstU32<512>>, _>(
+				|_, _, old| {
+					translated += 1;
+					let new: Vec<HealthCheckV1> = old.into_iter().map(Into::into).collect();
+					Some(BoundedVec::truncate_from(new))
+				},
+			);
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Migrated {} health check histories to v1", translated);
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((HealthChecks::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				HealthChecks::<T>::iter_values().count() as u32 == count,
+				"health check histories lost in migration",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 1, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Adds the network a `Service` belongs to, and the reported genesis hash to `HealthCheck`.
+/// Existing services are left without a network until the admin sets it with
+/// `set_service_network`.
+pub mod v2 {
+	use super::{v1::HealthCheckV1, *};
+
+	#[derive(Decode)]
+	struct OldService {
+		id: u32,
+		ty: ServiceType,
+		name: BoundedVec<u8, ConstU32<64>>,
+		url_path: BoundedVec<u8, ConstU32<32>>,
+	}
+
+	/// `Services` as of storage version 2.
+	#[frame_support::storage_alias]
+	pub(super) type Services<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, u32, ServiceV2>;
+
+	/// `Service` as of storage version 2.
+	#[derive(Encode, Decode)]
+	pub(super) struct ServiceV2 {
+		pub id: u32,
+		pub ty: ServiceType,
+		pub name: BoundedVec<u8, ConstU32<64>>,
+		pub url_path: BoundedVec<u8, ConstU32<32>>,
+		pub network_id: Option<u32>,
+	}
+
+	/// `HealthChecks` as of storage version 2.
+	#[frame_support::storage_alias]
+	pub(super) type HealthChecks<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		u32,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		BoundedVec<HealthCheckV2, ConstU32<512>>,
+	>;
+
+	/// `HealthCheck` as of storage version 2.
+	#[derive(Encode, Decode)]
+	pub(super) struct HealthCheckV2 {
+		pub member_service_id: u32,
+		pub timestamp: u64,
+		pub result: HealthCheckResult,
+		pub response_time_ms: u32,
+		pub best_block: Option<u32>,
+		pub finalized_block: Option<u32>,
+		pub peer_count: Option<u32>,
+		pub is_syncing: Option<bool>,
+		pub genesis_hash: Option<H256>,
+	}
+
+	impl From<HealthCheckV1> for HealthCheckV2 {
+		fn from(old: HealthCheckV1) -> Self {
+			HealthCheckV2 {
+				member_service_id: old.member_service_id,
+				timestamp: old.timestamp,
+				result: old.result,
+				response_time_ms: old.response_time_ms,
+				best_block: old.best_block,
+				finalized_block: old.finalized_block,
+				peer_count: old.peer_count,
+				is_syncing: old.is_syncing,
+				genesis_hash: None,
+			}
+		}
+	}
+
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 1 {
+				log::info!(target: LOG_TARGET, "v2 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut translated = 0u64;
+			Services::<T>::translate::<OldService, _>(|_, old| {
+				translated += 1;
+				Some(ServiceV2 {
+					id: old.id,
+					ty: old.ty,
+					name: old.name,
+					url_path: old.url_path,
+					network_id: None,
+				})
+			});
+			HealthChecks::<T>::translate::<BoundedVec<HealthCheckV1, ConstU32<512>>, _>(
+				|_, _, old| {
+					translated += 1;
+					let new: Vec<HealthCheckV2> = old.into_iter().map(Into::into).collect();
+					Some(BoundedVec::truncate_from(new))
+				},
+			);
+			StorageVersion::new(2).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Migrated {} services and histories to v2", translated);
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let services = Services::<T>::iter_keys().count() as u32;
+			let histories = HealthChecks::<T>::iter_keys().count() as u32;
+			Ok((services, histories).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (services, histories) =
+				<(u32, u32)>::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				Services::<T>::iter_values().count() as u32 == services,
+				"services lost in migration",
+			);
+			ensure!(
+				HealthChecks::<T>::iter_values().count() as u32 == histories,
+				"health check histories lost in migration",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 2, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Adds probe parameters to `Service`, set to the defaults of the service's type.
+pub mod v3 {
+	use super::{v2::ServiceV2, *};
+
+	/// `Services` as of storage version 3.
+	#[frame_support::storage_alias]
+	pub(super) type Services<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, u32, ServiceV3>;
+
+	/// `Service` as of storage version 3.
+	#[derive(Encode, Decode)]
+	pub(super) struct ServiceV3 {
+		pub id: u32,
+		pub ty: ServiceType,
+		pub name: BoundedVec<u8, ConstU32<64>>,
+		pub url_path: BoundedVec<u8, ConstU32<32>>,
+		pub network_id: Option<u32>,
+		pub probe: ProbeParams,
+	}
+
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 2 {
+				log::info!(target: LOG_TARGET, "v3 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut translated = 0u64;
+			Services::<T>::translate::<ServiceV2, _>(|_, old| {
+				translated += 1;
+				let probe = ProbeParams::default_for(&old.ty);
+				Some(ServiceV3 {
+					id: old.id,
+					ty: old.ty,
+					name: old.name,
+					url_path: old.url_path,
+					network_id: old.network_id,
+					probe,
+				})
+			});
+			StorageVersion::new(3).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Migrated {} services to v3", translated);
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((Services::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				Services::<T>::iter_values().count() as u32 == count,
+				"services lost in migration",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 3, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Adds the bootnode address to `MemberService`, unset for existing member services.
+pub mod v4 {
+	use super::*;
+
+	#[derive(Decode)]
+	struct OldMemberService {
+		service_id: u32,
+		member_id: u32,
+		id: u32,
+		name: BoundedVec<u8, ConstU32<64>>,
+		address: BoundedVec<u8, ConstU32<128>>,
+		port: u16,
+	}
+
+	pub struct MigrateToV4<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV4<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 3 {
+				log::info!(target: LOG_TARGET, "v4 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut translated = 0u64;
+			MemberServices::<T>::translate::<OldMemberService, _>(|_, old| {
+				translated += 1;
+				Some(MemberService {
+					service_id: old.service_id,
+					member_id: old.member_id,
+					id: old.id,
+					name: old.name,
+					address: old.address,
+					port: old.port,
+					bootnode: None,
+				})
+			});
+			StorageVersion::new(4).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Migrated {} member services to v4", translated);
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((MemberServices::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				MemberServices::<T>::iter_values().count() as u32 == count,
+				"member services lost in migration",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 4, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Adds the archive block to `Network`, unset for existing networks.
+pub mod v5 {
+	use super::*;
+
+	#[derive(Decode)]
+	struct OldNetwork {
+		id: u32,
+		name: BoundedVec<u8, ConstU32<64>>,
+		genesis_hash: H256,
+		relay_network_id: Option<u32>,
+		para_id: Option<u32>,
+		ss58_prefix: u16,
+		token_symbol: BoundedVec<u8, ConstU32<8>>,
+	}
+
+	pub struct MigrateToV5<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV5<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 4 {
+				log::info!(target: LOG_TARGET, "v5 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut translated = 0u64;
+			Networks::<T>::translate::<OldNetwork, _>(|_, old| {
+				translated += 1;
+				Some(Network {
+					id: old.id,
+					name: old.name,
+					genesis_hash: old.genesis_hash,
+					relay_network_id: old.relay_network_id,
+					para_id: old.para_id,
+					ss58_prefix: old.ss58_prefix,
+					token_symbol: old.token_symbol,
+					archive_block_hash: None,
+				})
+			});
+			StorageVersion::new(5).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Migrated {} networks to v5", translated);
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((Networks::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				Networks::<T>::iter_values().count() as u32 == count,
+				"networks lost in migration",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 5, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Adds the reported TLS certificate to `HealthCheck`, unset for existing health checks.
+pub mod v6 {
+	use super::{v2::HealthCheckV2, *};
+
+	impl From<HealthCheckV2> for HealthCheck {
+		fn from(old: HealthCheckV2) -> Self {
+			HealthCheck {
+				member_service_id: old.member_service_id,
+				timestamp: old.timestamp,
+				result: old.result,
+				response_time_ms: old.response_time_ms,
+				best_block: old.best_block,
+				finalized_block: old.finalized_block,
+				peer_count: old.peer_count,
+				is_syncing: old.is_syncing,
+				genesis_hash: old.genesis_hash,
+				certificate: None,
+			}
+		}
+	}
+
+	pub struct MigrateToV6<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV6<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 5 {
+				log::info!(target: LOG_TARGET, "v6 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut translated = 0u64;
+			HealthChecks::<T>::translate::<BoundedVec<HealthCheckV2, ConstU32<512>>, _>(
+				|_, _, old| {
+					translated += 1;
+					let new: Vec<HealthCheck> = old.into_iter().map(Into::into).collect();
+					Some(BoundedVec::truncate_from(new))
+				},
+			);
+			StorageVersion::new(6).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Migrated {} health check histories to v6", translated);
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((HealthChecks::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				HealthChecks::<T>::iter_values().count() as u32 == count,
+				"health check histories lost in migration",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 6, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Marks the member services registered before domain challenges existed as verified, so that
+/// their health checks keep being accepted.
+pub mod v7 {
+	use super::*;
+
+	pub struct MigrateToV7<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV7<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 6 {
+				log::info!(target: LOG_TARGET, "v7 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut verified = 0u64;
+			for member_service_id in MemberServices::<T>::iter_keys() {
+				VerificationStatuses::<T>::insert(member_service_id, VerificationStatus::Verified);
+				verified += 1;
+			}
+			StorageVersion::new(7).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Marked {} member services verified in v7", verified);
+			T::DbWeight::get().reads_writes(verified + 1, verified + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((MemberServices::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				VerificationStatuses::<T>::iter_values()
+					.filter(|status| *status == VerificationStatus::Verified)
+					.count() as u32 == count,
+				"member services not marked verified",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 7, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Initializes `MembersChanged` with the members registered so far.
+pub mod v8 {
+	use super::*;
+
+	pub struct MigrateToV8<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV8<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 7 {
+				log::info!(target: LOG_TARGET, "v8 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut members: Vec<T::AccountId> = Members::<T>::iter_keys().collect();
+			members.sort();
+			T::MembersChanged::set_members_sorted(&members, &[]);
+			StorageVersion::new(8).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Announced {} members in v8", members.len());
+			let count = members.len() as u64;
+			T::DbWeight::get().reads_writes(count + 1, 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(StorageVersion::get::<Pallet<T>>() == 8, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Adds the level to `Member` and the minimum level to `Service`, both zero for existing ones.
+pub mod v9 {
+	use super::{v3::ServiceV3, *};
+
+	#[derive(Decode)]
+	struct OldMember {
+		id: u32,
+		name: BoundedVec<u8, ConstU32<64>>,
+	}
+
+	pub struct MigrateToV9<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV9<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 8 {
+				log::info!(target: LOG_TARGET, "v9 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let mut translated = 0u64;
+			Members::<T>::translate::<OldMember, _>(|_, old| {
+				translated += 1;
+				Some(Member { id: old.id, name: old.name, level: 0 })
+			});
+			Services::<T>::translate::<ServiceV3, _>(|_, old| {
+				translated += 1;
+				Some(Service {
+					id: old.id,
+					ty: old.ty,
+					name: old.name,
+					url_path: old.url_path,
+					network_id: old.network_id,
+					probe: old.probe,
+					min_level: 0,
+				})
+			});
+			StorageVersion::new(9).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Migrated {} members and services to v9", translated);
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let members = Members::<T>::iter_keys().count() as u32;
+			let services = Services::<T>::iter_keys().count() as u32;
+			Ok((members, services).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (members, services) =
+				<(u32, u32)>::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				Members::<T>::iter_values().count() as u32 == members,
+				"members lost in migration",
+			);
+			ensure!(
+				Services::<T>::iter_values().count() as u32 == services,
+				"services lost in migration",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 9, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Schedules disputes opened before disputes could expire to expire `DisputeTimeout` blocks after
+/// the upgrade.
+pub mod v10 {
+	use super::*;
+
+	pub struct MigrateToV10<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV10<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 9 {
+				log::info!(target: LOG_TARGET, "v10 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let now = frame_system::Pallet::<T>::block_number();
+			let mut expires_at = now.saturating_add(T::DisputeTimeout::get().max(One::one()));
+			let mut scheduled = 0u64;
+			for id in Disputes::<T>::iter_keys() {
+				scheduled += 1;
+				// Spill over to the next blocks once a block is full.
+				while DisputeExpiries::<T>::try_mutate(expires_at, |ids| ids.try_push(id)).is_err()
+				{
+					expires_at = expires_at.saturating_add(One::one());
+				}
+			}
+			StorageVersion::new(10).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Scheduled the expiry of {} disputes", scheduled);
+			T::DbWeight::get().reads_writes(scheduled * 2 + 1, scheduled + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((Disputes::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let disputes = u32::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			let scheduled: u32 =
+				DisputeExpiries::<T>::iter_values().map(|ids| ids.len() as u32).sum();
+			ensure!(scheduled == disputes, "disputes left without an expiry");
+			ensure!(StorageVersion::get::<Pallet<T>>() == 10, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Counts the registered member services and gives the ones registered before member service
+/// deposits a deposit, reserved from their member if it can afford it. Deposits that can't be
+/// reserved are recorded as empty, which leaves nothing to slash.
+pub mod v11 {
+	use super::*;
+
+	pub struct MigrateToV11<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV11<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 10 {
+				log::info!(target: LOG_TARGET, "v11 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let accounts: BTreeMap<u32, T::AccountId> =
+				Members::<T>::iter().map(|(account, member)| (member.id, account)).collect();
+			let mut reads = accounts.len() as u64 + 1;
+			let mut writes = 2u64;
+			let mut active = 0u32;
+			let mut unfunded = 0u32;
+			for (id, member_service) in MemberServices::<T>::iter() {
+				active += 1;
+				reads += 2;
+				if MemberServiceDeposits::<T>::contains_key(id) {
+					continue
+				}
+				let depositor = match accounts.get(&member_service.member_id) {
+					Some(depositor) => depositor.clone(),
+					None => continue,
+				};
+				let deposit = T::MemberServiceDeposit::get();
+				let amount = match T::Currency::reserve(&depositor, deposit) {
+					Ok(()) => deposit,
+					Err(_) => {
+						unfunded += 1;
+						Zero::zero()
+					},
+				};
+				MemberServiceDeposits::<T>::insert(id, MemberServiceDeposit { depositor, amount });
+				writes += 2;
+			}
+			ActiveMemberServices::<T>::put(active);
+			StorageVersion::new(11).put::<Pallet<T>>();
+			log::info!(
+				target: LOG_TARGET,
+				"Migrated {} member services to v11, {} without a reserved deposit",
+				active,
+				unfunded,
+			);
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			let active = MemberServices::<T>::iter_keys().count() as u32;
+			ensure!(ActiveMemberServices::<T>::get() == active, "member services miscounted");
+			ensure!(StorageVersion::get::<Pallet<T>>() == 11, "storage version not updated");
+			Ok(())
+		}
+	}
+}
+
+/// Records the votes on applications and disputes by member id, so that they follow membership
+/// transfers, and indexes deposits, disputes and monitors by the account that holds them.
+pub mod v12 {
+	use super::*;
+
+	#[derive(Decode)]
+	struct OldApplication<AccountId, Balance, BlockNumber> {
+		name: BoundedVec<u8, ConstU32<64>>,
+		metadata: BoundedVec<u8, ConstU32<256>>,
+		deposit: Balance,
+		expires_at: BlockNumber,
+		ayes: BoundedVec<AccountId, ConstU32<32>>,
+		nays: BoundedVec<AccountId, ConstU32<32>>,
+	}
+
+	#[derive(Decode)]
+	struct OldDispute<AccountId, Balance, BlockNumber> {
+		member_service_id: u32,
+		disputant: AccountId,
+		target: DisputeTarget<AccountId>,
+		reports: BoundedVec<(AccountId, u64), ConstU32<64>>,
+		bond: Balance,
+		opened_at: BlockNumber,
+		ayes: BoundedVec<AccountId, ConstU32<32>>,
+		nays: BoundedVec<AccountId, ConstU32<32>>,
+	}
+
+	pub struct MigrateToV12<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV12<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() != 11 {
+				log::info!(target: LOG_TARGET, "v12 migration not applicable, skipping");
+				return T::DbWeight::get().reads(1)
+			}
+			let ids: BTreeMap<T::AccountId, u32> =
+				Members::<T>::iter().map(|(account, member)| (account, member.id)).collect();
+			// Votes of accounts that are no longer members are dropped.
+			let member_ids = |voters: BoundedVec<T::AccountId, ConstU32<32>>| {
+				BoundedVec::truncate_from(
+					voters.iter().filter_map(|voter| ids.get(voter).copied()).collect(),
+				)
+			};
+			let mut translated = 0u64;
+			Applications::<T>::translate::<
+				OldApplication<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+				_,
+			>(|_, old| {
+				translated += 1;
+				Some(Application {
+					name: old.name,
+					metadata: old.metadata,
+					deposit: old.deposit,
+					expires_at: old.expires_at,
+					ayes: member_ids(old.ayes),
+					nays: member_ids(old.nays),
+				})
+			});
+			Disputes::<T>::translate::<OldDispute<T::AccountId, BalanceOf<T>, T::BlockNumber>, _>(
+				|id, old| {
+					translated += 1;
+					if DisputesByDisputant::<T>::try_append(&old.disputant, id).is_err() {
+						log::warn!(target: LOG_TARGET, "Dispute {} left out of the index", id);
+					}
+					Some(Dispute {
+						member_service_id: old.member_service_id,
+						disputant: old.disputant,
+						target: old.target,
+						reports: old.reports,
+						bond: old.bond,
+						opened_at: old.opened_at,
+						ayes: member_ids(old.ayes),
+						nays: member_ids(old.nays),
+					})
+				},
+			);
+			let mut indexed = 0u64;
+			for (member_service_id, deposit) in MemberServiceDeposits::<T>::iter() {
+				indexed += 1;
+				if DepositsByDepositor::<T>::try_append(&deposit.depositor, member_service_id)
+					.is_err()
+				{
+					log::warn!(
+						target: LOG_TARGET,
+						"Deposit of member service {} left out of the index",
+						member_service_id,
+					);
+				}
+			}
+			for (monitor, owner) in MonitorOwners::<T>::iter() {
+				indexed += 1;
+				if MonitorsByOwner::<T>::try_append(&owner, &monitor).is_err() {
+					log::warn!(target: LOG_TARGET, "Monitor {:?} left out of the index", monitor);
+				}
+			}
+			StorageVersion::new(12).put::<Pallet<T>>();
+			log::info!(
+				target: LOG_TARGET,
+				"Migrated {} applications and disputes and indexed {} deposits and monitors to v12",
+				translated,
+				indexed,
+			);
+			let entries = translated + indexed;
+			T::DbWeight::get().reads_writes(ids.len() as u64 + 2 * entries + 1, 2 * entries + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let applications = Applications::<T>::iter_keys().count() as u32;
+			let disputes = Disputes::<T>::iter_keys().count() as u32;
+			Ok((applications, disputes).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let (applications, disputes) =
+				<(u32, u32)>::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+			ensure!(
+				Applications::<T>::iter_values().count() as u32 == applications,
+				"applications lost in migration",
+			);
+			ensure!(
+				Disputes::<T>::iter_values().count() as u32 == disputes,
+				"disputes lost in migration",
+			);
+			ensure!(StorageVersion::get::<Pallet<T>>() == 12, "storage version not updated");
+			Ok(())
+		}
+	}
+}
@@ -0,0 +1,180 @@
+use crate as pallet_ibp;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, ConstU8},
+	PalletId,
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, IdentifyAccount, IdentityLookup, Verify},
+	MultiSignature, Perbill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
+pub type AccountId = <<MultiSignature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system,
+		Balances: pallet_balances,
+		IBP: pallet_ibp,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type FreezeIdentifier = ();
+	type MaxFreezes = ();
+	type HoldIdentifier = ();
+	type MaxHolds = ();
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = <MultiSignature as Verify>::Signer;
+	type Signature = MultiSignature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+parameter_types! {
+	pub const HealthCheckInterval: u64 = 10;
+	pub const IbpPalletId: PalletId = PalletId(*b"ibp/pot_");
+	pub const SloUptime: Perbill = Perbill::from_percent(99);
+	pub const BreachSlash: Perbill = Perbill::from_percent(10);
+	pub static VerifiedAccounts: Vec<AccountId> = vec![];
+	pub static RequireIdentity: bool = false;
+	pub static MaxProbesPerBlock: u32 = 8;
+	pub static MaxProbeTimePerBlock: u64 = 30_000;
+}
+
+/// Verifies the identities of `VerifiedAccounts`.
+pub struct MockIdentityVerifier;
+
+impl pallet_ibp::VerifyIdentity<AccountId> for MockIdentityVerifier {
+	fn verified_identity(who: &AccountId) -> Option<pallet_ibp::VerifiedIdentity> {
+		VerifiedAccounts::get().contains(who).then(|| pallet_ibp::VerifiedIdentity {
+			display: b"Helikon".to_vec(),
+			web: b"https://helikon.io".to_vec(),
+			email: b"ibp@helikon.io".to_vec(),
+		})
+	}
+}
+
+impl pallet_ibp::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type WeightInfo = ();
+	type Currency = Balances;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type RegistrarOrigin = EnsureRoot<AccountId>;
+	type SlashOrigin = EnsureRoot<AccountId>;
+	type RootDispatchOrigin = EnsureRoot<AccountId>;
+	type RuntimeCall = RuntimeCall;
+	type MembersChanged = ();
+	type MaxMembers = ConstU32<3>;
+	type ApplicationDeposit = ConstU64<10>;
+	type ApplicationTimeout = ConstU64<20>;
+	type ApplicationVotesRequired = ConstU32<2>;
+	type MaxMemberLevel = ConstU8<3>;
+	type PromotionEras = ConstU32<2>;
+	type DemotionEras = ConstU32<2>;
+	type IdentityVerifier = MockIdentityVerifier;
+	type RequireIdentityForApproval = RequireIdentity;
+	type RequireIdentityForPromotion = RequireIdentity;
+	type HealthCheckReward = ConstU64<100>;
+	type HealthCheckInterval = HealthCheckInterval;
+	type AuthorityId = pallet_ibp::crypto::MonitorAuthId;
+	type UnsignedPriority = ConstU64<{ 1 << 20 }>;
+	type ProbeTimeout = ConstU64<5_000>;
+	type MaxProbesPerBlock = MaxProbesPerBlock;
+	type MaxProbeTimePerBlock = MaxProbeTimePerBlock;
+	type EraLength = ConstU64<100>;
+	type EraHistoryDepth = ConstU32<4>;
+	type MaxBlockLag = ConstU32<5>;
+	type CertificateExpiryWarningDays = ConstU32<14>;
+	type RequiredAttestations = ConstU32<1>;
+	type MaxMaintenancesPerEra = ConstU32<2>;
+	type MaxMaintenanceDuration = ConstU64<20>;
+	type MaintenanceNotice = ConstU64<10>;
+	type IncidentThreshold = ConstU32<3>;
+	type MaxIncidentHistory = ConstU32<2>;
+	type DisputeBond = ConstU64<50>;
+	type DisputeVotesRequired = ConstU32<2>;
+	type DisputeTimeout = ConstU64<50>;
+	type MemberServiceDeposit = ConstU64<100>;
+	type MaxMemberServices = ConstU32<4>;
+	type PalletId = IbpPalletId;
+	type SloUptime = SloUptime;
+	type SlashAfterBreaches = ConstU32<2>;
+	type SuspendAfterBreaches = ConstU32<3>;
+	type BreachSlash = BreachSlash;
+	type SlashDeferDuration = ConstU32<1>;
+	type MemberEraBudget = ConstU64<1_000>;
+	type TargetResponseTime = ConstU32<200>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![
+			(AccountId::new([1; 32]), 1_000),
+			(AccountId::new([3; 32]), 1_000),
+			(AccountId::new([5; 32]), 1_000),
+		],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+	let mut ext: sp_io::TestExternalities = storage.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,69 @@
+//! Validation of bootnode multiaddrs such as
+//! `/dns/boot.example.com/tcp/30333/p2p/12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp`.
+//!
+//! The runtime can't depend on libp2p, so this checks the structure clients rely on: a host, a TCP
+//! port, optionally `ws` or `wss`, and a trailing `/p2p/<peer-id>` in base58.
+
+use frame_support::sp_std::str;
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// The peer id of a valid bootnode multiaddr, or `None` if it isn't one.
+pub fn bootnode_peer_id(multiaddr: &[u8]) -> Option<&[u8]> {
+	let multiaddr = str::from_utf8(multiaddr).ok()?;
+	let mut components = multiaddr.strip_prefix('/')?.split('/');
+	let valid_host = match (components.next()?, components.next()?) {
+		("ip4", host) => is_ip4(host),
+		("ip6", host) => is_ip6(host),
+		("dns" | "dns4" | "dns6", host) => is_domain(host),
+		_ => false,
+	};
+	if !valid_host {
+		return None
+	}
+	match (components.next()?, components.next()?) {
+		("tcp", port) => port.parse::<u16>().ok().filter(|port| *port != 0)?,
+		_ => return None,
+	};
+	let mut protocol = components.next()?;
+	if protocol == "ws" || protocol == "wss" {
+		protocol = components.next()?;
+	}
+	let peer_id = components.next()?;
+	if protocol != "p2p" || components.next().is_some() || !is_peer_id(peer_id) {
+		return None
+	}
+	Some(peer_id.as_bytes())
+}
+
+fn is_ip4(host: &str) -> bool {
+	let mut octets = host.split('.');
+	octets.clone().count() == 4 &&
+		octets.all(|octet| !octet.is_empty() && octet.len() <= 3 && octet.parse::<u8>().is_ok())
+}
+
+fn is_ip6(host: &str) -> bool {
+	let mut groups = host.split(':');
+	let count = groups.clone().count();
+	(3..=8).contains(&count) &&
+		groups
+			.all(|group| group.len() <= 4 && group.bytes().all(|byte| byte.is_ascii_hexdigit()))
+}
+
+fn is_domain(host: &str) -> bool {
+	!host.is_empty() &&
+		host.len() <= 253 &&
+		host.split('.').all(|label| {
+			!label.is_empty() &&
+				label.len() <= 63 &&
+				!label.starts_with('-') &&
+				!label.ends_with('-') &&
+				label.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+		})
+}
+
+/// Base58 peer ids of Ed25519 keys are 52 characters long, those of RSA keys 46.
+fn is_peer_id(peer_id: &str) -> bool {
+	(46..=64).contains(&peer_id.len()) &&
+		peer_id.bytes().all(|byte| BASE58_ALPHABET.contains(&byte))
+}
@@ -0,0 +1,852 @@
+//! Offchain worker that probes member services from monitor nodes.
+//!
+//! A node is a monitor node if its keystore holds an `ibpm` key bound to a monitor with
+//! `set_monitor_key`. Every block, such a node probes the member services assigned to it that it
+//! hasn't checked within `HealthCheckInterval` over JSON-RPC and submits the results as unsigned
+//! health checks signed by the monitor key. Each member service is assigned to one monitor with a
+//! bound key per era, see [`Pallet::is_assigned`]. Probes are only started while at least
+//! `ProbeTimeout` of the run's `MaxProbeTimePerBlock` is left, so a run ends in time even if every
+//! probe times out. `system_health` decides the result and the response time, and a service's
+//! required RPC methods must be listed by `rpc_methods`. Archive services must serve
+//! `System.Number` at their network's archive block, or fail with `StateUnavailable`, as pruned
+//! nodes answer `system_health` just fine. The genesis hash and the best and finalized block
+//! numbers are queried afterwards on a best-effort basis. The pallet compares the genesis hash with
+//! the service's network.
+//!
+//! RPC services are probed here over the protocol of their probe parameters when that is HTTPS.
+//! IPFS gateways must serve the empty directory at [`IPFS_PROBE_PATH`] over HTTPS.
+//!
+//! The offchain HTTP API can't open WebSockets. The worker publishes the assigned endpoints probed
+//! over WSS, such as WSS-only RPC and telemetry, under [`WEBSOCKET_TARGETS_KEY`], along with the
+//! archive block of archive services, the node's WebSocket prober stores a [`WebSocketProbeResult`]
+//! under [`websocket_result_key`] for each, and the worker submits those results like any other
+//! health check.
+//!
+//! Bootnodes have to be dialed with libp2p, which the runtime can't do. The worker publishes the
+//! assigned bootnodes to probe under [`BOOTNODE_TARGETS_KEY`] in persistent offchain storage, the
+//! node's bootnode prober stores a [`BootNodeProbeResult`] under [`bootnode_result_key`] for each,
+//! and the worker submits those results like any other health check.
+//!
+//! Member services are only probed once verified. Until then, the worker looks for the token of
+//! their domain challenge at `https://<address>/.well-known/ibp-challenge`, or in the TXT record of
+//! `_ibp-challenge.<address>` through DNS over HTTPS, and attests the challenge if it finds it.
+//! Monitors don't attest the member services of their own member.
+//!
+//! TLS certificates aren't visible through the offchain HTTP API either. The worker publishes the
+//! assigned RPC endpoints under [`CERTIFICATE_TARGETS_KEY`], the node's certificate prober stores a
+//! [`CertificateReport`] under [`certificate_report_key`] for each, and the worker attaches it to
+//! the next health check of the member service.
+
+use crate::{
+	Call, CertificateReport, Config, DomainAttestationPayload, DomainChallenges, FailureCode,
+	HealthCheck, HealthCheckPayload, HealthCheckResult, LastHealthCheck, MemberService,
+	MemberServices, MonitorKeyOf, Networks, Pallet, ProbeParams, Protocol, Service, ServiceType,
+	Services,
+};
+use alloc::{format, string::String};
+use codec::{Decode, Encode};
+use frame_support::{
+	pallet_prelude::ConstU32,
+	sp_std::{prelude::*, str},
+	traits::Get,
+	BoundedVec,
+};
+use frame_system::offchain::{AppCrypto, SendUnsignedTransaction, Signer};
+use lite_json::{JsonObject, JsonValue};
+use sp_core::{hexdisplay::HexDisplay, H256};
+use sp_runtime::{
+	offchain::{
+		http,
+		storage::{MutateStorageError, StorageRetrievalError, StorageValueRef},
+		Duration, StorageKind, Timestamp,
+	},
+	traits::{SaturatedConversion, Saturating},
+	RuntimeAppPublic,
+};
+
+const LOG_TARGET: &str = "runtime::ibp";
+
+pub(crate) const SYSTEM_HEALTH_REQUEST: &[u8] =
+	br#"{"id":1,"jsonrpc":"2.0","method":"system_health","params":[]}"#;
+pub(crate) const RPC_METHODS_REQUEST: &[u8] =
+	br#"{"id":1,"jsonrpc":"2.0","method":"rpc_methods","params":[]}"#;
+pub(crate) const CHAIN_GET_GENESIS_HASH_REQUEST: &[u8] =
+	br#"{"id":1,"jsonrpc":"2.0","method":"chain_getBlockHash","params":[0]}"#;
+pub(crate) const CHAIN_GET_HEADER_REQUEST: &[u8] =
+	br#"{"id":1,"jsonrpc":"2.0","method":"chain_getHeader","params":[]}"#;
+pub(crate) const CHAIN_GET_FINALIZED_HEAD_REQUEST: &[u8] =
+	br#"{"id":1,"jsonrpc":"2.0","method":"chain_getFinalizedHead","params":[]}"#;
+
+/// Default DNS over HTTPS resolver that serves `application/dns-json`, used to look up the TXT
+/// records of domain challenges.
+pub(crate) const DNS_OVER_HTTPS_URL: &str = "https://cloudflare-dns.com/dns-query";
+
+/// Persistent offchain storage key of a monitor's own DNS over HTTPS resolver URL, as raw UTF-8,
+/// which replaces [`DNS_OVER_HTTPS_URL`]. Set it with the `offchain_localStorageSet` RPC.
+pub const DNS_OVER_HTTPS_URL_KEY: &[u8] = b"ibp::dns_over_https::url";
+
+/// Persistent offchain storage key of the `Vec<BootNodeTarget>` the bootnode prober dials.
+pub const BOOTNODE_TARGETS_KEY: &[u8] = b"ibp::bootnode::targets";
+
+/// Persistent offchain storage key of the latest [`BootNodeProbeResult`] of a member service.
+pub fn bootnode_result_key(member_service_id: u32) -> Vec<u8> {
+	(b"ibp::bootnode::result", member_service_id).encode()
+}
+
+/// Persistent offchain storage key of the `Vec<WebSocketTarget>` the WebSocket prober connects to.
+pub const WEBSOCKET_TARGETS_KEY: &[u8] = b"ibp::websocket::targets";
+
+/// Persistent offchain storage key of the latest [`WebSocketProbeResult`] of a member service.
+pub fn websocket_result_key(member_service_id: u32) -> Vec<u8> {
+	(b"ibp::websocket::result", member_service_id).encode()
+}
+
+/// Path of the empty UnixFS directory, which every IPFS gateway can resolve.
+pub(crate) const IPFS_PROBE_PATH: &str =
+	"/ipfs/bafybeiczsscdsbs7ffqz55asqdf3smv6klcw3gofszvwlyarci47bgf354";
+
+/// Persistent offchain storage key of the `Vec<CertificateTarget>` the certificate prober connects
+/// to.
+pub const CERTIFICATE_TARGETS_KEY: &[u8] = b"ibp::certificate::targets";
+
+/// Persistent offchain storage key of the latest [`CertificateReport`] of a member service.
+pub fn certificate_report_key(member_service_id: u32) -> Vec<u8> {
+	(b"ibp::certificate::report", member_service_id).encode()
+}
+
+const MAX_PROBE_TARGETS: usize = 128;
+
+/// A TLS endpoint for the node's certificate prober to connect to.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+pub struct CertificateTarget {
+	pub member_service_id: u32,
+	pub host: Vec<u8>,
+	pub port: u16,
+}
+
+/// A bootnode for the node's bootnode prober to dial.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+pub struct BootNodeTarget {
+	pub member_service_id: u32,
+	/// Multiaddr ending in `/p2p/<peer_id>`.
+	pub multiaddr: Vec<u8>,
+}
+
+/// A WebSocket endpoint for the node's WebSocket prober to connect to.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+pub struct WebSocketTarget {
+	pub member_service_id: u32,
+	/// `wss://` URL of the endpoint.
+	pub url: Vec<u8>,
+	/// Whether the endpoint serves JSON-RPC, whose health and genesis hash are queried once
+	/// connected.
+	pub rpc: bool,
+	/// RPC methods the endpoint must expose in `rpc_methods`.
+	pub required_methods: Vec<Vec<u8>>,
+	/// Block at which archive endpoints must serve the storage under [`system_number_key`].
+	pub archive_block_hash: Option<H256>,
+}
+
+/// Outcome of probing a WebSocket endpoint, stored by the node's WebSocket prober.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+pub struct WebSocketProbeResult {
+	/// Unix time the probe started at, in milliseconds.
+	pub timestamp: u64,
+	pub result: HealthCheckResult,
+	/// Time until the handshake completed, or until `system_health` answered for RPC endpoints.
+	pub response_time_ms: u32,
+	pub peer_count: Option<u32>,
+	pub is_syncing: Option<bool>,
+	pub genesis_hash: Option<H256>,
+}
+
+/// Outcome of dialing a bootnode, stored by the node's bootnode prober.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+pub struct BootNodeProbeResult {
+	/// Unix time the dial started at, in milliseconds.
+	pub timestamp: u64,
+	pub result: HealthCheckResult,
+	/// Time until the libp2p handshake completed.
+	pub response_time_ms: u32,
+}
+
+/// Storage key of `System.Number`, which archive probes query at the archive block.
+pub fn system_number_key() -> Vec<u8> {
+	[sp_io::hashing::twox_128(b"System"), sp_io::hashing::twox_128(b"Number")].concat()
+}
+
+/// `state_getStorage` request for `System.Number` at the block with the given hash.
+pub(crate) fn state_get_system_number_request(block_hash: H256) -> Vec<u8> {
+	format!(
+		r#"{{"id":1,"jsonrpc":"2.0","method":"state_getStorage","params":["0x{}","0x{}"]}}"#,
+		HexDisplay::from(&system_number_key()),
+		HexDisplay::from(block_hash.as_fixed_bytes()),
+	)
+	.into_bytes()
+}
+
+/// `chain_getHeader` request for the block with the given hash.
+pub(crate) fn chain_get_header_request(hash: &str) -> Vec<u8> {
+	format!(r#"{{"id":1,"jsonrpc":"2.0","method":"chain_getHeader","params":["{}"]}}"#, hash)
+		.into_bytes()
+}
+
+impl<T: Config> Pallet<T> {
+	/// Probe due member services and submit the results, if this node holds a bound monitor key.
+	pub(crate) fn probe_member_services(block_number: T::BlockNumber) {
+		let (public, monitor) = match Self::local_monitor() {
+			Some(local_monitor) => local_monitor,
+			None => {
+				log::trace!(target: LOG_TARGET, "No bound monitor key in the keystore.");
+				return
+			},
+		};
+		let run_deadline =
+			sp_io::offchain::timestamp().add(Duration::from_millis(T::MaxProbeTimePerBlock::get()));
+		let monitors = MonitorKeyOf::<T>::iter_keys().collect::<Vec<_>>();
+		let era = Self::era_of(block_number);
+		// Unverified member services may point at anything, so the node's probers don't dial them
+		// either.
+		let assigned = |member_service_id| {
+			Self::is_verified(member_service_id) &&
+				Self::is_assigned(&monitor, &monitors, member_service_id, era)
+		};
+		Self::publish_probe_targets(&assigned);
+		let interval = T::HealthCheckInterval::get();
+		let mut due = MemberServices::<T>::iter_values()
+			.filter(|member_service| {
+				assigned(member_service.id) &&
+					LastHealthCheck::<T>::get(member_service.id, &monitor)
+						.map_or(true, |last| block_number >= last.saturating_add(interval))
+			})
+			.filter_map(|member_service| {
+				let service = Services::<T>::get(member_service.service_id)?;
+				// Wait for the node's probers rather than claiming the probe without a result.
+				if Self::awaits_node_prober(member_service.id, &service) {
+					return None
+				}
+				Some((member_service, service))
+			})
+			// Claimed before `take`, so probes claimed by an earlier run that aren't included yet
+			// don't use up the slots of this block.
+			.filter(|(member_service, _)| {
+				let claim_key = (b"ibp::probe", member_service.id).encode();
+				Self::claim(&claim_key, block_number, interval)
+			})
+			.take(T::MaxProbesPerBlock::get() as usize);
+		// Checked before `next`, which claims the probe.
+		while Self::probe_fits(run_deadline) {
+			let (member_service, service) = match due.next() {
+				Some(due) => due,
+				None => break,
+			};
+			let health_check = match Self::probe(&member_service, &service) {
+				Some(health_check) => health_check,
+				None => continue,
+			};
+			let result = Signer::<T, T::AuthorityId>::any_account()
+				.with_filter(vec![public.clone()])
+				.send_unsigned_transaction(
+					|account| HealthCheckPayload {
+						public: account.public.clone(),
+						block_number,
+						health_check: health_check.clone(),
+					},
+					|payload, signature| Call::submit_health_check_unsigned { payload, signature },
+				);
+			if let Some((_, Err(()))) | None = result {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to submit health check for member service {}.",
+					member_service.id,
+				);
+			}
+		}
+		Self::attest_domains(&public, &monitor, block_number, run_deadline);
+	}
+
+	/// Whether `monitor` is the one among `monitors` assigned to probe `member_service_id` in
+	/// `era`: the one with the highest `blake2_256((monitor, member_service_id, era))`. Every
+	/// member service gets one monitor per era, and the assignments are reshuffled every era.
+	pub(crate) fn is_assigned(
+		monitor: &T::AccountId,
+		monitors: &[T::AccountId],
+		member_service_id: u32,
+		era: u32,
+	) -> bool {
+		monitors
+			.iter()
+			.max_by_key(|candidate| {
+				sp_io::hashing::blake2_256(&(candidate, member_service_id, era).encode())
+			})
+			.map_or(false, |assigned| assigned == monitor)
+	}
+
+	/// Whether a probe started now ends before `run_deadline`, even if it times out.
+	fn probe_fits(run_deadline: Timestamp) -> bool {
+		sp_io::offchain::timestamp().add(Duration::from_millis(T::ProbeTimeout::get())) <=
+			run_deadline
+	}
+
+	/// Look for the tokens of the domain challenges `monitor` may attest and submit attestations
+	/// for those found published.
+	fn attest_domains(
+		public: &T::Public,
+		monitor: &T::AccountId,
+		block_number: T::BlockNumber,
+		run_deadline: Timestamp,
+	) {
+		let interval = T::HealthCheckInterval::get();
+		let pending = DomainChallenges::<T>::iter()
+			.filter(|(member_service_id, challenge)| {
+				Self::validate_domain_attestation(monitor, *member_service_id, challenge.token)
+					.is_ok()
+			})
+			.take(T::MaxProbesPerBlock::get() as usize);
+		for (member_service_id, challenge) in pending {
+			if !Self::probe_fits(run_deadline) {
+				break
+			}
+			let member_service = match MemberServices::<T>::get(member_service_id) {
+				Some(member_service) => member_service,
+				None => continue,
+			};
+			let claim_key = (b"ibp::challenge", member_service_id).encode();
+			if !Self::claim(&claim_key, block_number, interval) {
+				continue
+			}
+			let published = str::from_utf8(&member_service.address)
+				.map_or(false, |address| Self::challenge_published(address, challenge.token));
+			if !published {
+				continue
+			}
+			let result = Signer::<T, T::AuthorityId>::any_account()
+				.with_filter(vec![public.clone()])
+				.send_unsigned_transaction(
+					|account| DomainAttestationPayload {
+						public: account.public.clone(),
+						block_number,
+						member_service_id,
+						token: challenge.token,
+					},
+					|payload, signature| Call::submit_domain_attestation_unsigned {
+						payload,
+						signature,
+					},
+				);
+			if let Some((_, Err(()))) | None = result {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to submit domain attestation for member service {}.",
+					member_service_id,
+				);
+			}
+		}
+	}
+
+	/// Whether `token` is published at the well-known path of `address` or in its TXT record.
+	pub(crate) fn challenge_published(address: &str, token: H256) -> bool {
+		let deadline =
+			sp_io::offchain::timestamp().add(Duration::from_millis(T::ProbeTimeout::get()));
+		let well_known = format!("https://{}/.well-known/ibp-challenge", address);
+		let published = http_get(&well_known, "text/plain", deadline)
+			.and_then(|body| String::from_utf8(body).ok())
+			.and_then(|body| parse_hash(body.trim()));
+		if published == Some(token) {
+			return true
+		}
+		let query = format!("{}?name=_ibp-challenge.{}&type=TXT", dns_over_https_url(), address);
+		let response = match http_get(&query, "application/dns-json", deadline)
+			.and_then(|body| String::from_utf8(body).ok())
+			.and_then(|body| lite_json::parse_json(&body).ok())
+		{
+			Some(JsonValue::Object(response)) => response,
+			_ => return false,
+		};
+		let answers = match field(&response, "Answer") {
+			Some(JsonValue::Array(answers)) => answers,
+			_ => return false,
+		};
+		answers.iter().any(|answer| {
+			let data = match answer {
+				JsonValue::Object(answer) => field(answer, "data").and_then(as_string),
+				_ => None,
+			};
+			// TXT record data is quoted.
+			data.and_then(|data| parse_hash(data.trim_matches('"'))) == Some(token)
+		})
+	}
+
+	/// The first key in the keystore that is bound to a monitor, with that monitor.
+	fn local_monitor() -> Option<(T::Public, T::AccountId)> {
+		<T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all()
+			.into_iter()
+			.find_map(|key| {
+				let generic: <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic =
+					key.into();
+				let public: T::Public = generic.into();
+				Self::monitor_of_key(&public).map(|monitor| (public, monitor))
+			})
+	}
+
+	/// Records under `key` in offchain storage that this node is probing something at
+	/// `block_number`. Returns `false` if it already did so within `interval`, e.g. because the
+	/// previous health check hasn't been included yet.
+	fn claim(key: &[u8], block_number: T::BlockNumber, interval: T::BlockNumber) -> bool {
+		let storage = StorageValueRef::persistent(key);
+		let claimed = storage.mutate(
+			|last: Result<Option<T::BlockNumber>, StorageRetrievalError>| match last {
+				Ok(Some(last)) if block_number < last.saturating_add(interval) => Err(()),
+				_ => Ok(block_number),
+			},
+		);
+		match claimed {
+			Ok(_) => true,
+			Err(MutateStorageError::ValueFunctionFailed(())) => false,
+			Err(MutateStorageError::ConcurrentModification(_)) => false,
+		}
+	}
+
+	/// Publish the endpoints of the `assigned` member services the node's bootnode, WebSocket and
+	/// certificate probers connect to.
+	fn publish_probe_targets(assigned: &impl Fn(u32) -> bool) {
+		Self::publish_bootnode_targets(assigned);
+		Self::publish_websocket_targets(assigned);
+		let targets = MemberServices::<T>::iter_values()
+			.filter(|member_service| {
+				assigned(member_service.id) &&
+					Services::<T>::get(member_service.service_id)
+						.map_or(false, |service| service.ty.is_rpc())
+			})
+			.map(|member_service| CertificateTarget {
+				member_service_id: member_service.id,
+				host: member_service.address.into_inner(),
+				port: member_service.port,
+			})
+			.take(MAX_PROBE_TARGETS)
+			.collect::<Vec<_>>();
+		StorageValueRef::persistent(CERTIFICATE_TARGETS_KEY).set(&targets);
+	}
+
+	/// Publish the `assigned` bootnodes with a registered address for the node's bootnode prober.
+	fn publish_bootnode_targets(assigned: &impl Fn(u32) -> bool) {
+		let targets = MemberServices::<T>::iter_values()
+			.filter(|member_service| assigned(member_service.id))
+			.filter_map(|member_service| {
+				let bootnode = member_service.bootnode?;
+				Some(BootNodeTarget {
+					member_service_id: member_service.id,
+					multiaddr: bootnode.multiaddr.into_inner(),
+				})
+			})
+			.take(MAX_PROBE_TARGETS)
+			.collect::<Vec<_>>();
+		StorageValueRef::persistent(BOOTNODE_TARGETS_KEY).set(&targets);
+	}
+
+	/// Publish the `assigned` endpoints probed over WSS for the node's WebSocket prober.
+	fn publish_websocket_targets(assigned: &impl Fn(u32) -> bool) {
+		let targets = MemberServices::<T>::iter_values()
+			.filter(|member_service| assigned(member_service.id))
+			.filter_map(|member_service| {
+				let service = Services::<T>::get(member_service.service_id)?;
+				if service.ty.is_bootnode() || service.probe.protocol != Protocol::Wss {
+					return None
+				}
+				let address = str::from_utf8(&member_service.address).ok()?;
+				let url_path = str::from_utf8(&service.url_path).ok()?;
+				let archive_block_hash = Self::archive_block_hash(&service);
+				Some(WebSocketTarget {
+					member_service_id: member_service.id,
+					url: format!("wss://{}:{}{}", address, member_service.port, url_path)
+						.into_bytes(),
+					rpc: service.ty.is_rpc(),
+					required_methods: service
+						.probe
+						.required_methods
+						.into_iter()
+						.map(BoundedVec::into_inner)
+						.collect(),
+					archive_block_hash,
+				})
+			})
+			.take(MAX_PROBE_TARGETS)
+			.collect::<Vec<_>>();
+		StorageValueRef::persistent(WEBSOCKET_TARGETS_KEY).set(&targets);
+	}
+
+	/// Whether a member service is probed by one of the node's probers, which hasn't stored a
+	/// result yet.
+	fn awaits_node_prober(member_service_id: u32, service: &Service) -> bool {
+		if service.ty.is_bootnode() {
+			let key = bootnode_result_key(member_service_id);
+			!matches!(StorageValueRef::persistent(&key).get::<BootNodeProbeResult>(), Ok(Some(_)))
+		} else if service.probe.protocol == Protocol::Wss {
+			let key = websocket_result_key(member_service_id);
+			!matches!(StorageValueRef::persistent(&key).get::<WebSocketProbeResult>(), Ok(Some(_)))
+		} else {
+			false
+		}
+	}
+
+	/// Take the result the bootnode prober stored for a member service.
+	fn take_bootnode_result(member_service_id: u32) -> Option<HealthCheck> {
+		let key = bootnode_result_key(member_service_id);
+		let mut storage = StorageValueRef::persistent(&key);
+		let probe_result = storage.get::<BootNodeProbeResult>().ok()??;
+		storage.clear();
+		Some(HealthCheck {
+			member_service_id,
+			timestamp: probe_result.timestamp,
+			result: probe_result.result,
+			response_time_ms: probe_result.response_time_ms,
+			best_block: None,
+			finalized_block: None,
+			peer_count: None,
+			is_syncing: None,
+			genesis_hash: None,
+			certificate: None,
+		})
+	}
+
+	/// Take the result the WebSocket prober stored for a member service.
+	fn take_websocket_result(member_service_id: u32) -> Option<HealthCheck> {
+		let key = websocket_result_key(member_service_id);
+		let mut storage = StorageValueRef::persistent(&key);
+		let probe_result = storage.get::<WebSocketProbeResult>().ok()??;
+		storage.clear();
+		Some(HealthCheck {
+			member_service_id,
+			timestamp: probe_result.timestamp,
+			result: probe_result.result,
+			response_time_ms: probe_result.response_time_ms,
+			best_block: None,
+			finalized_block: None,
+			peer_count: probe_result.peer_count,
+			is_syncing: probe_result.is_syncing,
+			genesis_hash: probe_result.genesis_hash,
+			certificate: Self::take_certificate_report(member_service_id),
+		})
+	}
+
+	/// Take the report the certificate prober stored for a member service.
+	fn take_certificate_report(member_service_id: u32) -> Option<CertificateReport> {
+		let key = certificate_report_key(member_service_id);
+		let mut storage = StorageValueRef::persistent(&key);
+		let report = storage.get::<CertificateReport>().ok()??;
+		storage.clear();
+		Some(report)
+	}
+
+	/// Probe a member service, over the protocol of its probe parameters. Returns `None` if it
+	/// can't be probed with those, or its address isn't valid UTF-8.
+	fn probe(member_service: &MemberService, service: &Service) -> Option<HealthCheck> {
+		if service.ty.is_bootnode() {
+			return Self::take_bootnode_result(member_service.id)
+		}
+		if service.probe.protocol == Protocol::Wss {
+			return Self::take_websocket_result(member_service.id)
+		}
+		if service.probe.protocol != Protocol::Https {
+			return None
+		}
+		let address = str::from_utf8(&member_service.address).ok()?;
+		let url_path = str::from_utf8(&service.url_path).ok()?;
+		let url = format!("https://{}:{}{}", address, member_service.port, url_path);
+		if service.ty == ServiceType::IpfsGateway {
+			return Some(Self::probe_ipfs_gateway(member_service.id, &url))
+		}
+		if !service.ty.is_rpc() {
+			return None
+		}
+		let mut health_check = Self::probe_rpc(
+			member_service.id,
+			&url,
+			&service.probe,
+			Self::archive_block_hash(service),
+		);
+		health_check.certificate = Self::take_certificate_report(member_service.id);
+		Some(health_check)
+	}
+
+	/// The archive block of the network of a service probed as an archive.
+	fn archive_block_hash(service: &Service) -> Option<H256> {
+		if !service.probe.archive {
+			return None
+		}
+		service
+			.network_id
+			.and_then(Networks::<T>::get)
+			.and_then(|network| network.archive_block_hash)
+	}
+
+	/// Check that the IPFS gateway at `url` serves the empty directory.
+	pub(crate) fn probe_ipfs_gateway(member_service_id: u32, url: &str) -> HealthCheck {
+		let started = sp_io::offchain::timestamp();
+		let deadline = started.add(Duration::from_millis(T::ProbeTimeout::get()));
+		let url = format!("{}{}", url, IPFS_PROBE_PATH);
+		let response = http::Request::get(&url)
+			.deadline(deadline)
+			.send()
+			.map_err(|_| FailureCode::Connection)
+			.and_then(|pending| Self::wait(pending, deadline));
+		let result = match response {
+			Ok(response) if response.code == 200 => HealthCheckResult::Ok,
+			Ok(response) => HealthCheckResult::Failed(FailureCode::HttpStatus(response.code)),
+			Err(code) => HealthCheckResult::Failed(code),
+		};
+		HealthCheck {
+			member_service_id,
+			timestamp: started.unix_millis(),
+			result,
+			response_time_ms: sp_io::offchain::timestamp()
+				.diff(&started)
+				.millis()
+				.saturated_into::<u32>(),
+			best_block: None,
+			finalized_block: None,
+			peer_count: None,
+			is_syncing: None,
+			genesis_hash: None,
+			certificate: None,
+		}
+	}
+
+	/// Check the health of the RPC endpoint at `url`.
+	pub(crate) fn probe_rpc(
+		member_service_id: u32,
+		url: &str,
+		params: &ProbeParams,
+		archive_block_hash: Option<H256>,
+	) -> HealthCheck {
+		let started = sp_io::offchain::timestamp();
+		let deadline = started.add(Duration::from_millis(T::ProbeTimeout::get()));
+		let health = Self::rpc_request(url, SYSTEM_HEALTH_REQUEST.to_vec(), deadline);
+		let mut health_check = HealthCheck {
+			member_service_id,
+			timestamp: started.unix_millis(),
+			result: HealthCheckResult::Ok,
+			response_time_ms: sp_io::offchain::timestamp()
+				.diff(&started)
+				.millis()
+				.saturated_into::<u32>(),
+			best_block: None,
+			finalized_block: None,
+			peer_count: None,
+			is_syncing: None,
+			genesis_hash: None,
+			certificate: None,
+		};
+		let health = match health {
+			Ok(JsonValue::Object(health)) => health,
+			Ok(_) => {
+				health_check.result = HealthCheckResult::Failed(FailureCode::InvalidResponse);
+				return health_check
+			},
+			Err(code) => {
+				health_check.result = HealthCheckResult::Failed(code);
+				return health_check
+			},
+		};
+		health_check.peer_count = field(&health, "peers").and_then(as_u32);
+		health_check.is_syncing = match field(&health, "isSyncing") {
+			Some(JsonValue::Boolean(is_syncing)) => Some(*is_syncing),
+			_ => None,
+		};
+		if !params.required_methods.is_empty() {
+			if let Err(code) = Self::check_methods(url, &params.required_methods, deadline) {
+				health_check.result = HealthCheckResult::Failed(code);
+				return health_check
+			}
+		}
+		if let Some(block_hash) = archive_block_hash {
+			if let Err(code) = Self::check_archive_state(url, block_hash, deadline) {
+				health_check.result = HealthCheckResult::Failed(code);
+				return health_check
+			}
+		}
+		health_check.genesis_hash =
+			Self::rpc_request(url, CHAIN_GET_GENESIS_HASH_REQUEST.to_vec(), deadline)
+				.ok()
+				.and_then(|hash| as_string(&hash))
+				.and_then(|hash| parse_hash(&hash));
+		health_check.best_block =
+			Self::rpc_request(url, CHAIN_GET_HEADER_REQUEST.to_vec(), deadline)
+				.ok()
+				.and_then(|header| header_number(&header));
+		health_check.finalized_block =
+			Self::rpc_request(url, CHAIN_GET_FINALIZED_HEAD_REQUEST.to_vec(), deadline)
+				.ok()
+				.and_then(|hash| as_string(&hash))
+				.and_then(|hash| {
+					Self::rpc_request(url, chain_get_header_request(&hash), deadline).ok()
+				})
+				.and_then(|header| header_number(&header));
+		health_check
+	}
+
+	/// Check that `rpc_methods` lists all `required` methods.
+	fn check_methods(
+		url: &str,
+		required: &[BoundedVec<u8, ConstU32<64>>],
+		deadline: Timestamp,
+	) -> Result<(), FailureCode> {
+		let methods = match Self::rpc_request(url, RPC_METHODS_REQUEST.to_vec(), deadline)? {
+			JsonValue::Object(result) => match field(&result, "methods") {
+				Some(JsonValue::Array(methods)) =>
+					methods.iter().filter_map(as_string).collect::<Vec<_>>(),
+				_ => return Err(FailureCode::InvalidResponse),
+			},
+			_ => return Err(FailureCode::InvalidResponse),
+		};
+		let missing = required
+			.iter()
+			.any(|method| !methods.iter().any(|available| available.as_bytes() == &method[..]));
+		if missing {
+			return Err(FailureCode::MissingRpcMethod)
+		}
+		Ok(())
+	}
+
+	/// Check that the endpoint serves `System.Number` at `block_hash`. Pruned nodes answer with
+	/// `null` or an error.
+	fn check_archive_state(
+		url: &str,
+		block_hash: H256,
+		deadline: Timestamp,
+	) -> Result<(), FailureCode> {
+		match Self::rpc_request(url, state_get_system_number_request(block_hash), deadline) {
+			Ok(JsonValue::String(_)) => Ok(()),
+			Ok(JsonValue::Null) | Err(FailureCode::JsonRpcError(_)) =>
+				Err(FailureCode::StateUnavailable),
+			Ok(_) => Err(FailureCode::InvalidResponse),
+			Err(code) => Err(code),
+		}
+	}
+
+	/// Wait for the response to a request until `deadline`.
+	fn wait(
+		pending: http::PendingRequest,
+		deadline: Timestamp,
+	) -> Result<http::Response, FailureCode> {
+		pending
+			.try_wait(deadline)
+			.map_err(|_| FailureCode::Timeout)?
+			.map_err(|error| match error {
+				http::Error::DeadlineReached => FailureCode::Timeout,
+				http::Error::IoError | http::Error::Unknown => FailureCode::Connection,
+			})
+	}
+
+	/// POST a JSON-RPC request and return the `result` of the response.
+	fn rpc_request(
+		url: &str,
+		body: Vec<u8>,
+		deadline: Timestamp,
+	) -> Result<JsonValue, FailureCode> {
+		let pending = http::Request::post(url, vec![body])
+			.add_header("Content-Type", "application/json")
+			.deadline(deadline)
+			.send()
+			.map_err(|_| FailureCode::Connection)?;
+		let response = Self::wait(pending, deadline)?;
+		if response.code != 200 {
+			return Err(FailureCode::HttpStatus(response.code))
+		}
+		let body = response.body().collect::<Vec<u8>>();
+		let json = str::from_utf8(&body)
+			.ok()
+			.and_then(|body| lite_json::parse_json(body).ok())
+			.ok_or(FailureCode::InvalidResponse)?;
+		let response = match json {
+			JsonValue::Object(response) => response,
+			_ => return Err(FailureCode::InvalidResponse),
+		};
+		if let Some(JsonValue::Object(error)) = field(&response, "error") {
+			let code = field(error, "code").and_then(as_i32).unwrap_or_default();
+			return Err(FailureCode::JsonRpcError(code))
+		}
+		response
+			.into_iter()
+			.find(|(key, _)| key.iter().copied().eq("result".chars()))
+			.map(|(_, result)| result)
+			.ok_or(FailureCode::InvalidResponse)
+	}
+}
+
+/// The DNS over HTTPS resolver set under [`DNS_OVER_HTTPS_URL_KEY`], or [`DNS_OVER_HTTPS_URL`].
+fn dns_over_https_url() -> String {
+	sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, DNS_OVER_HTTPS_URL_KEY)
+		.and_then(|url| String::from_utf8(url).ok())
+		.map(|url| String::from(url.trim()))
+		.filter(|url| url.starts_with("https://"))
+		.unwrap_or_else(|| DNS_OVER_HTTPS_URL.into())
+}
+
+/// GET `url` and return the body of a successful response.
+fn http_get(url: &str, accept: &str, deadline: Timestamp) -> Option<Vec<u8>> {
+	let pending = http::Request::get(url)
+		.add_header("Accept", accept)
+		.deadline(deadline)
+		.send()
+		.ok()?;
+	let response = pending.try_wait(deadline).ok()?.ok()?;
+	if response.code != 200 {
+		return None
+	}
+	Some(response.body().collect())
+}
+
+fn field<'a>(object: &'a JsonObject, name: &str) -> Option<&'a JsonValue> {
+	object
+		.iter()
+		.find(|(key, _)| key.iter().copied().eq(name.chars()))
+		.map(|(_, value)| value)
+}
+
+fn as_u32(value: &JsonValue) -> Option<u32> {
+	match value {
+		JsonValue::Number(number) if !number.negative => number.integer.try_into().ok(),
+		_ => None,
+	}
+}
+
+fn as_i32(value: &JsonValue) -> Option<i32> {
+	match value {
+		JsonValue::Number(number) => {
+			let integer: i32 = number.integer.try_into().ok()?;
+			Some(if number.negative { -integer } else { integer })
+		},
+		_ => None,
+	}
+}
+
+fn as_string(value: &JsonValue) -> Option<String> {
+	match value {
+		JsonValue::String(chars) => Some(chars.iter().collect()),
+		_ => None,
+	}
+}
+
+/// Parse a `0x`-prefixed, hex encoded 32 byte hash.
+fn parse_hash(hash: &str) -> Option<H256> {
+	let hex = hash.strip_prefix("0x")?.as_bytes();
+	if hex.len() != 64 {
+		return None
+	}
+	let mut bytes = [0u8; 32];
+	for (byte, pair) in bytes.iter_mut().zip(hex.chunks(2)) {
+		*byte = u8::from_str_radix(str::from_utf8(pair).ok()?, 16).ok()?;
+	}
+	Some(H256(bytes))
+}
+
+/// Block number of a JSON-RPC header, which is hex encoded.
+fn header_number(header: &JsonValue) -> Option<u32> {
+	let header = match header {
+		JsonValue::Object(header) => header,
+		_ => return None,
+	};
+	let number = as_string(field(header, "number")?)?;
+	u32::from_str_radix(number.strip_prefix("0x")?, 16).ok()
+}
@@ -0,0 +1,2484 @@
+use crate::{
+	bootnode_result_key, crypto,
+	mock::*,
+	offchain::{
+		chain_get_header_request, state_get_system_number_request,
+		CHAIN_GET_FINALIZED_HEAD_REQUEST, CHAIN_GET_GENESIS_HASH_REQUEST, CHAIN_GET_HEADER_REQUEST,
+		IPFS_PROBE_PATH, RPC_METHODS_REQUEST, SYSTEM_HEALTH_REQUEST,
+	},
+	websocket_result_key, BootNodeProbeResult, BootNodeTarget, Call, CertificateReport,
+	CertificateTarget, CheckHealthCheck, DomainAttestationPayload, DomainChallenges, Error,
+	ExpectedCertificate, FailureCode, HealthCheck, HealthCheckPayload, HealthCheckResult,
+	HealthChecks, ProbeParams, Protocol, SecurityIncidentKind, ServiceType, VerificationStatus,
+	WebSocketProbeResult, WebSocketTarget, BOOTNODE_TARGETS_KEY, CERTIFICATE_TARGETS_KEY,
+	DNS_OVER_HTTPS_URL_KEY, HEALTH_CHECK_TOO_FREQUENT, MILLISECS_PER_DAY, WEBSOCKET_TARGETS_KEY,
+};
+use codec::Decode;
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::GetDispatchInfo,
+	pallet_prelude::*,
+	traits::{Currency, Hooks, OnRuntimeUpgrade, ReservableCurrency},
+};
+use frame_system::offchain::SignedPayload;
+use sp_core::{
+	offchain::{
+		testing::{self, OffchainState, PendingRequest, PoolState},
+		OffchainDbExt, OffchainWorkerExt, StorageKind, TransactionPoolExt,
+	},
+	sr25519, H256,
+};
+use sp_keystore::{testing::MemoryKeystore, Keystore, KeystoreExt};
+use sp_runtime::{
+	offchain::storage::StorageValueRef,
+	traits::{IdentifyAccount, SignedExtension},
+	AccountId32, DispatchError, MultiSigner, Perbill, RuntimeAppPublic,
+};
+use std::sync::Arc;
+
+const PHRASE: &str = "news slush supreme milk chapter athlete soap sausage put clutch what kitten";
+const RPC_URL: &str = "https://rpc.helikon.io:443/polkadot";
+const POLKADOT_GENESIS: &str = "0x91b171bb158e2d3848fa23a9f1c25182fb8e20313b2c1eb49219da7a70ce90c3";
+const KUSAMA_GENESIS: &str = "0xb0a8d493285c2df73290dfb7e61f870f17b41801197a149ca93654499ea3dafe";
+
+type OffchainStateRef = Arc<parking_lot::RwLock<OffchainState>>;
+type PoolStateRef = Arc<parking_lot::RwLock<PoolState>>;
+
+fn bounded<S: Get<u32>>(value: &[u8]) -> BoundedVec<u8, S> {
+	value.to_vec().try_into().unwrap()
+}
+
+fn genesis(hash: &str) -> H256 {
+	hash.parse().unwrap()
+}
+
+fn member() -> AccountId32 {
+	AccountId32::new([1; 32])
+}
+
+fn monitor() -> AccountId32 {
+	AccountId32::new([2; 32])
+}
+
+/// Admit `account` as a member through an application approved by the admin.
+fn add_member(account: AccountId32, name: &[u8]) {
+	assert_ok!(IBP::apply_for_membership(
+		RuntimeOrigin::signed(account.clone()),
+		bounded(name),
+		bounded(b""),
+	));
+	assert_ok!(IBP::approve_application(RuntimeOrigin::root(), account));
+}
+
+/// Test externalities with offchain, transaction pool and keystore extensions, and an `ibpm` key.
+fn offchain_test_ext() -> (sp_io::TestExternalities, OffchainStateRef, PoolStateRef, sr25519::Public)
+{
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let keystore = MemoryKeystore::new();
+	let key = keystore
+		.sr25519_generate_new(crypto::Public::ID, Some(&format!("{}/monitor", PHRASE)))
+		.unwrap();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt::new(keystore));
+	(ext, offchain_state, pool_state, key)
+}
+
+/// Register an RPC service, a member serving it at `RPC_URL` and a monitor, optionally bound to
+/// `key`.
+fn register_member_service(key: Option<sr25519::Public>) {
+	register_member_service_of(ServiceType::RPC, key);
+}
+
+/// Register a service of type `ty`, a member serving it at `RPC_URL` and a monitor, optionally
+/// bound to `key`.
+fn register_member_service_of(ty: ServiceType, key: Option<sr25519::Public>) {
+	assert_ok!(IBP::register_network(
+		RuntimeOrigin::root(),
+		bounded(b"Polkadot"),
+		genesis(POLKADOT_GENESIS),
+		None,
+		None,
+		0,
+		bounded(b"DOT"),
+	));
+	assert_ok!(IBP::register_service(
+		RuntimeOrigin::root(),
+		ty,
+		bounded(b"Polkadot RPC"),
+		bounded(b"/polkadot"),
+		0,
+	));
+	add_member(member(), b"Helikon");
+	assert_ok!(IBP::register_member_service(
+		RuntimeOrigin::signed(member()),
+		0,
+		bounded(b"helikon-polkadot-rpc"),
+		bounded(b"rpc.helikon.io"),
+		443,
+	));
+	assert_ok!(IBP::set_verification_status(
+		RuntimeOrigin::root(),
+		0,
+		VerificationStatus::Verified,
+	));
+	assert_ok!(IBP::register_monitor(
+		RuntimeOrigin::signed(member()),
+		monitor(),
+		bounded(b"monitor"),
+	));
+	if let Some(key) = key {
+		assert_ok!(IBP::set_monitor_key(
+			RuntimeOrigin::signed(member()),
+			monitor(),
+			MultiSigner::from(key),
+		));
+	}
+}
+
+fn expect_rpc_request(state: &OffchainStateRef, body: &[u8], response: &[u8]) {
+	state.write().expect_request(PendingRequest {
+		method: "POST".into(),
+		uri: RPC_URL.into(),
+		headers: vec![("Content-Type".into(), "application/json".into())],
+		body: body.to_vec(),
+		response: Some(response.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+const FINALIZED_HASH: &str = "0x1f2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f809";
+
+/// Expect the requests of a probe of a node with the given `system_health` result and genesis
+/// hash.
+fn expect_probe(state: &OffchainStateRef, health: &str, genesis_hash: &str) {
+	expect_health(state, health);
+	expect_chain_queries(state, genesis_hash);
+}
+
+fn expect_health(state: &OffchainStateRef, health: &str) {
+	expect_rpc_request(
+		state,
+		SYSTEM_HEALTH_REQUEST,
+		format!(r#"{{"jsonrpc":"2.0","result":{},"id":1}}"#, health).as_bytes(),
+	);
+}
+
+/// Expect the best-effort queries of the genesis hash and the best and finalized block numbers.
+fn expect_chain_queries(state: &OffchainStateRef, genesis_hash: &str) {
+	expect_rpc_request(
+		state,
+		CHAIN_GET_GENESIS_HASH_REQUEST,
+		format!(r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#, genesis_hash).as_bytes(),
+	);
+	expect_rpc_request(
+		state,
+		CHAIN_GET_HEADER_REQUEST,
+		br#"{"jsonrpc":"2.0","result":{"number":"0x10e1"},"id":1}"#,
+	);
+	expect_rpc_request(
+		state,
+		CHAIN_GET_FINALIZED_HEAD_REQUEST,
+		format!(r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#, FINALIZED_HASH).as_bytes(),
+	);
+	expect_rpc_request(
+		state,
+		&chain_get_header_request(FINALIZED_HASH),
+		br#"{"jsonrpc":"2.0","result":{"number":"0x10de"},"id":1}"#,
+	);
+}
+
+/// Decode the only transaction in the pool into the submitted health check.
+fn submitted_health_check(
+	pool_state: &PoolStateRef,
+) -> (HealthCheckPayload<MultiSigner, u64>, sp_runtime::MultiSignature) {
+	let tx = pool_state.write().transactions.pop().unwrap();
+	assert!(pool_state.read().transactions.is_empty());
+	let tx = Extrinsic::decode(&mut &*tx).unwrap();
+	assert_eq!(tx.signature, None);
+	match tx.call {
+		RuntimeCall::IBP(Call::submit_health_check_unsigned { payload, signature }) =>
+			(payload, signature),
+		call => panic!("unexpected call: {:?}", call),
+	}
+}
+
+#[test]
+fn offchain_worker_submits_healthy_check() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_probe(&offchain_state, r#"{"peers":12,"isSyncing":false}"#, POLKADOT_GENESIS);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		IBP::offchain_worker(1);
+		let (payload, signature) = submitted_health_check(&pool_state);
+		assert_eq!(payload.public, MultiSigner::from(key));
+		assert_eq!(payload.health_check.member_service_id, 0);
+		assert_eq!(payload.health_check.result, HealthCheckResult::Ok);
+		assert_eq!(payload.health_check.peer_count, Some(12));
+		assert_eq!(payload.health_check.is_syncing, Some(false));
+		assert_eq!(payload.health_check.best_block, Some(4321));
+		assert_eq!(payload.health_check.finalized_block, Some(4318));
+		assert_eq!(payload.health_check.genesis_hash, Some(genesis(POLKADOT_GENESIS)));
+		assert!(SignedPayload::<Test>::verify::<crypto::MonitorAuthId>(&payload, signature));
+	});
+}
+
+#[test]
+fn offchain_worker_reports_rpc_error_as_down() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_rpc_request(
+		&offchain_state,
+		SYSTEM_HEALTH_REQUEST,
+		br#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#,
+	);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		IBP::offchain_worker(1);
+		let (payload, _) = submitted_health_check(&pool_state);
+		assert_eq!(
+			payload.health_check.result,
+			HealthCheckResult::Failed(FailureCode::JsonRpcError(-32601)),
+		);
+	});
+}
+
+#[test]
+fn syncing_node_is_recorded_as_down() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_probe(&offchain_state, r#"{"peers":3,"isSyncing":true}"#, POLKADOT_GENESIS);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		IBP::offchain_worker(1);
+		let (payload, signature) = submitted_health_check(&pool_state);
+		assert_eq!(payload.health_check.result, HealthCheckResult::Ok);
+		assert_ok!(IBP::submit_health_check_unsigned(RuntimeOrigin::none(), payload, signature));
+		let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+		assert_eq!(history[0].result, HealthCheckResult::Failed(FailureCode::Syncing));
+		assert!(!history[0].is_up());
+	});
+}
+
+#[test]
+fn offchain_worker_does_nothing_without_bound_key() {
+	let (mut ext, _, pool_state, _) = offchain_test_ext();
+	ext.execute_with(|| {
+		register_member_service(None);
+		IBP::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn offchain_worker_probes_once_per_interval() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_probe(&offchain_state, r#"{"peers":12,"isSyncing":false}"#, POLKADOT_GENESIS);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		IBP::offchain_worker(1);
+		// The first health check isn't included yet; no second request is expected.
+		IBP::offchain_worker(2);
+		submitted_health_check(&pool_state);
+	});
+}
+
+#[test]
+fn claimed_probes_do_not_take_up_probe_slots() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_probe(&offchain_state, r#"{"peers":12,"isSyncing":false}"#, POLKADOT_GENESIS);
+	offchain_state.write().expect_request(PendingRequest {
+		method: "POST".into(),
+		uri: "https://rpc2.helikon.io:443/polkadot".into(),
+		headers: vec![("Content-Type".into(), "application/json".into())],
+		body: SYSTEM_HEALTH_REQUEST.to_vec(),
+		response: Some(
+			br#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"-"},"id":1}"#.to_vec(),
+		),
+		sent: true,
+		..Default::default()
+	});
+	ext.execute_with(|| {
+		MaxProbesPerBlock::set(1);
+		register_member_service(Some(key));
+		assert_ok!(IBP::register_member_service(
+			RuntimeOrigin::signed(member()),
+			0,
+			bounded(b"helikon-polkadot-rpc-2"),
+			bounded(b"rpc2.helikon.io"),
+			443,
+		));
+		assert_ok!(IBP::set_verification_status(
+			RuntimeOrigin::root(),
+			1,
+			VerificationStatus::Verified,
+		));
+		IBP::offchain_worker(1);
+		assert_eq!(submitted_health_check(&pool_state).0.health_check.member_service_id, 0);
+		// Member service 0 is still claimed, so its slot goes to member service 1.
+		IBP::offchain_worker(2);
+		assert_eq!(submitted_health_check(&pool_state).0.health_check.member_service_id, 1);
+	});
+}
+
+#[test]
+fn probes_are_only_started_with_time_left_in_the_run() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_probe(&offchain_state, r#"{"peers":12,"isSyncing":false}"#, POLKADOT_GENESIS);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		// Less than the `ProbeTimeout` of a single probe.
+		MaxProbeTimePerBlock::set(4_000);
+		IBP::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+		// The skipped probe wasn't claimed, so the next run takes it.
+		MaxProbeTimePerBlock::set(30_000);
+		IBP::offchain_worker(2);
+		assert_eq!(submitted_health_check(&pool_state).0.health_check.member_service_id, 0);
+	});
+}
+
+#[test]
+fn member_services_are_assigned_to_one_monitor_per_era() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_probe(&offchain_state, r#"{"peers":12,"isSyncing":false}"#, POLKADOT_GENESIS);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		let other_monitor = AccountId32::new([4; 32]);
+		assert_ok!(IBP::register_monitor(
+			RuntimeOrigin::signed(member()),
+			other_monitor.clone(),
+			bounded(b"other monitor"),
+		));
+		assert_ok!(IBP::set_monitor_key(
+			RuntimeOrigin::signed(member()),
+			other_monitor.clone(),
+			MultiSigner::from(sr25519::Public::from_raw([7; 32])),
+		));
+		let monitors = [monitor(), other_monitor];
+		assert!(!IBP::is_assigned(&monitor(), &monitors, 0, 0));
+		assert!(IBP::is_assigned(&monitors[1], &monitors, 0, 0));
+		assert!(IBP::is_assigned(&monitor(), &monitors, 0, 1));
+
+		// Era 0 is the other monitor's to probe, so nothing is probed or published.
+		IBP::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+		assert_eq!(
+			StorageValueRef::persistent(CERTIFICATE_TARGETS_KEY).get::<Vec<CertificateTarget>>(),
+			Ok(Some(vec![])),
+		);
+		// Era 1 is this monitor's.
+		IBP::offchain_worker(101);
+		assert_eq!(submitted_health_check(&pool_state).0.health_check.member_service_id, 0);
+	});
+}
+
+#[test]
+fn unverified_member_services_are_not_published_to_the_node_probers() {
+	let (mut ext, offchain_state, _, key) = offchain_test_ext();
+	ext.execute_with(|| {
+		register_unverified_member_service(Some(key));
+		let mut state = offchain_state.write();
+		state.expect_request(PendingRequest {
+			method: "GET".into(),
+			uri: "https://rpc.helikon.io/.well-known/ibp-challenge".into(),
+			headers: vec![("Accept".into(), "text/plain".into())],
+			response: Some(b"Not Found".to_vec()),
+			sent: true,
+			..Default::default()
+		});
+		state.expect_request(PendingRequest {
+			method: "GET".into(),
+			uri: format!(
+				"{}?name=_ibp-challenge.rpc.helikon.io&type=TXT",
+				crate::offchain::DNS_OVER_HTTPS_URL
+			),
+			headers: vec![("Accept".into(), "application/dns-json".into())],
+			response: Some(br#"{"Status":3}"#.to_vec()),
+			sent: true,
+			..Default::default()
+		});
+		drop(state);
+		IBP::offchain_worker(1);
+		assert_eq!(
+			StorageValueRef::persistent(CERTIFICATE_TARGETS_KEY).get::<Vec<CertificateTarget>>(),
+			Ok(Some(vec![])),
+		);
+
+		assert_ok!(IBP::set_verification_status(
+			RuntimeOrigin::root(),
+			0,
+			VerificationStatus::Verified,
+		));
+		expect_probe(&offchain_state, r#"{"peers":12,"isSyncing":false}"#, POLKADOT_GENESIS);
+		IBP::offchain_worker(2);
+		let targets = StorageValueRef::persistent(CERTIFICATE_TARGETS_KEY)
+			.get::<Vec<CertificateTarget>>()
+			.unwrap()
+			.unwrap();
+		assert_eq!(targets.len(), 1);
+		assert_eq!(targets[0].member_service_id, 0);
+	});
+}
+
+#[test]
+fn unsigned_health_check_is_validated_and_rate_limited() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_probe(&offchain_state, r#"{"peers":12,"isSyncing":false}"#, POLKADOT_GENESIS);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		IBP::offchain_worker(1);
+		let (payload, signature) = submitted_health_check(&pool_state);
+		let call = Call::submit_health_check_unsigned {
+			payload: payload.clone(),
+			signature: signature.clone(),
+		};
+		assert_ok!(IBP::validate_unsigned(TransactionSource::Local, &call));
+		assert_ok!(IBP::submit_health_check_unsigned(RuntimeOrigin::none(), payload, signature));
+		assert_eq!(HealthChecks::<Test>::get(0, monitor()).unwrap().len(), 1);
+		assert_eq!(
+			IBP::validate_unsigned(TransactionSource::Local, &call),
+			InvalidTransaction::Custom(crate::HEALTH_CHECK_TOO_FREQUENT).into(),
+		);
+	});
+}
+
+#[test]
+fn set_monitor_key_rotates_key() {
+	new_test_ext().execute_with(|| {
+		let old_key = MultiSigner::from(sr25519::Public::from_raw([3; 32]));
+		let new_key = MultiSigner::from(sr25519::Public::from_raw([4; 32]));
+		register_member_service(None);
+		assert_noop!(
+			IBP::set_monitor_key(
+				RuntimeOrigin::signed(AccountId32::new([9; 32])),
+				monitor(),
+				old_key.clone(),
+			),
+			Error::<Test>::NotMonitorOwner,
+		);
+		assert_ok!(IBP::set_monitor_key(
+			RuntimeOrigin::signed(member()),
+			monitor(),
+			old_key.clone()
+		));
+		assert_ok!(IBP::set_monitor_key(
+			RuntimeOrigin::signed(monitor()),
+			monitor(),
+			new_key.clone()
+		));
+		assert_eq!(IBP::monitor_of_key(&old_key), None);
+		assert_eq!(IBP::monitor_of_key(&new_key), Some(monitor()));
+		assert_eq!(
+			new_key.clone().into_account(),
+			crate::MonitorKeyOf::<Test>::get(monitor()).unwrap()
+		);
+
+		// Re-binding the key a monitor already holds changes nothing.
+		assert_ok!(IBP::set_monitor_key(
+			RuntimeOrigin::signed(monitor()),
+			monitor(),
+			new_key.clone()
+		));
+		assert_eq!(IBP::monitor_of_key(&new_key), Some(monitor()));
+		let other_monitor = AccountId32::new([5; 32]);
+		assert_ok!(IBP::register_monitor(
+			RuntimeOrigin::signed(member()),
+			other_monitor.clone(),
+			bounded(b"other monitor"),
+		));
+		assert_noop!(
+			IBP::set_monitor_key(RuntimeOrigin::signed(member()), other_monitor, new_key),
+			Error::<Test>::MonitorKeyInUse,
+		);
+	});
+}
+
+#[test]
+fn signed_health_checks_are_free_and_rate_limited_in_the_pool() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		let call = RuntimeCall::IBP(Call::submit_health_check {
+			health_check: up_health_check(0, 100, 98),
+		});
+		let info = call.get_dispatch_info();
+		assert_eq!(info.pays_fee, Pays::No);
+		let check = CheckHealthCheck::<Test>::new();
+		assert_eq!(
+			check.validate(&AccountId32::new([9; 32]), &call, &info, 0),
+			InvalidTransaction::BadSigner.into(),
+		);
+		assert_ok!(check.validate(&monitor(), &call, &info, 0));
+		assert_ok!(check.clone().pre_dispatch(&monitor(), &call, &info, 0));
+
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			up_health_check(0, 100, 98),
+		));
+		let too_frequent = InvalidTransaction::Custom(HEALTH_CHECK_TOO_FREQUENT);
+		assert_eq!(check.validate(&monitor(), &call, &info, 0), too_frequent.into());
+		assert_eq!(
+			check.clone().pre_dispatch(&monitor(), &call, &info, 0),
+			Err(too_frequent.into())
+		);
+
+		System::set_block_number(1 + HealthCheckInterval::get());
+		assert_ok!(check.validate(&monitor(), &call, &info, 0));
+	});
+}
+
+#[test]
+fn health_check_extension_passes_other_calls_through() {
+	new_test_ext().execute_with(|| {
+		let call = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+		let info = call.get_dispatch_info();
+		let check = CheckHealthCheck::<Test>::new();
+		let who = AccountId32::new([9; 32]);
+		assert_eq!(check.validate(&who, &call, &info, 0), Ok(ValidTransaction::default()));
+		assert_ok!(check.pre_dispatch(&who, &call, &info, 0));
+	});
+}
+
+#[test]
+fn endpoint_serving_another_chain_is_recorded_as_wrong_chain() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_probe(&offchain_state, r#"{"peers":12,"isSyncing":false}"#, KUSAMA_GENESIS);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		IBP::offchain_worker(1);
+		let (payload, signature) = submitted_health_check(&pool_state);
+		assert_ok!(IBP::submit_health_check_unsigned(RuntimeOrigin::none(), payload, signature));
+		let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+		assert_eq!(history[0].result, HealthCheckResult::Failed(FailureCode::WrongChain));
+	});
+}
+
+#[test]
+fn rpc_check_without_genesis_hash_is_recorded_as_invalid_response() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		let mut health_check = up_health_check(0, 100, 98);
+		health_check.genesis_hash = None;
+		assert_ok!(IBP::submit_health_check(RuntimeOrigin::signed(monitor()), health_check));
+		let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+		assert_eq!(history[0].result, HealthCheckResult::Failed(FailureCode::InvalidResponse));
+	});
+}
+
+#[test]
+fn probe_params_default_to_the_service_type() {
+	let default_protocol = |ty| ProbeParams::default_for(&ty).protocol;
+	assert_eq!(default_protocol(ServiceType::RPC), Protocol::Https);
+	assert_eq!(default_protocol(ServiceType::HttpRPC), Protocol::Https);
+	assert_eq!(default_protocol(ServiceType::IpfsGateway), Protocol::Https);
+	assert_eq!(default_protocol(ServiceType::WssRPC), Protocol::Wss);
+	assert_eq!(default_protocol(ServiceType::Telemetry), Protocol::Wss);
+	assert_eq!(default_protocol(ServiceType::BootNode), Protocol::Libp2p);
+	assert_eq!(default_protocol(ServiceType::LightClientBootNode), Protocol::Libp2pWss);
+	assert!(ProbeParams::default_for(&ServiceType::ArchiveRPC).archive);
+	assert!(!ProbeParams::default_for(&ServiceType::RPC).archive);
+	for ty in [
+		ServiceType::RPC,
+		ServiceType::BootNode,
+		ServiceType::ArchiveRPC,
+		ServiceType::WssRPC,
+		ServiceType::HttpRPC,
+		ServiceType::LightClientBootNode,
+		ServiceType::IpfsGateway,
+		ServiceType::Telemetry,
+	] {
+		assert!(ProbeParams::default_for(&ty).supports(&ty));
+	}
+}
+
+#[test]
+fn set_probe_params_rejects_params_monitors_cant_probe_with() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		let probe = |protocol, required_methods: &[&[u8]], archive| ProbeParams {
+			protocol,
+			required_methods: required_methods
+				.iter()
+				.map(|method| bounded::<ConstU32<64>>(method))
+				.collect::<Vec<_>>()
+				.try_into()
+				.unwrap(),
+			archive,
+		};
+		assert_noop!(
+			IBP::set_probe_params(
+				RuntimeOrigin::signed(member()),
+				0,
+				probe(Protocol::Wss, &[], false)
+			),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			IBP::set_probe_params(RuntimeOrigin::root(), 0, probe(Protocol::Libp2p, &[], false)),
+			Error::<Test>::UnsupportedProbeParams,
+		);
+		let params = probe(Protocol::Wss, &[b"state_traceBlock"], false);
+		assert_ok!(IBP::set_probe_params(RuntimeOrigin::root(), 0, params.clone()));
+		assert_eq!(crate::Services::<Test>::get(0).unwrap().probe, params);
+		System::assert_has_event(crate::Event::ProbeParamsSet { service_id: 0 }.into());
+
+		assert_ok!(IBP::register_service(
+			RuntimeOrigin::root(),
+			ServiceType::Telemetry,
+			bounded(b"Telemetry"),
+			bounded(b"/submit"),
+			0,
+		));
+		assert_noop!(
+			IBP::set_probe_params(
+				RuntimeOrigin::root(),
+				1,
+				probe(Protocol::Wss, &[b"system_health"], false)
+			),
+			Error::<Test>::UnsupportedProbeParams,
+		);
+		assert_noop!(
+			IBP::set_probe_params(RuntimeOrigin::root(), 1, probe(Protocol::Wss, &[], true)),
+			Error::<Test>::UnsupportedProbeParams,
+		);
+	});
+}
+
+#[test]
+fn missing_rpc_method_fails_the_probe() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_health(&offchain_state, r#"{"peers":12,"isSyncing":false}"#);
+	expect_rpc_request(
+		&offchain_state,
+		RPC_METHODS_REQUEST,
+		br#"{"jsonrpc":"2.0","result":{"methods":["system_health","chain_getHeader"]},"id":1}"#,
+	);
+	ext.execute_with(|| {
+		register_member_service(Some(key));
+		assert_ok!(IBP::set_probe_params(
+			RuntimeOrigin::root(),
+			0,
+			ProbeParams {
+				protocol: Protocol::Https,
+				required_methods: vec![bounded(b"state_traceBlock")].try_into().unwrap(),
+				archive: false,
+			},
+		));
+		IBP::offchain_worker(1);
+		let (payload, _) = submitted_health_check(&pool_state);
+		assert_eq!(
+			payload.health_check.result,
+			HealthCheckResult::Failed(FailureCode::MissingRpcMethod),
+		);
+	});
+}
+
+#[test]
+fn wss_endpoints_are_probed_by_the_node() {
+	let (mut ext, _, pool_state, key) = offchain_test_ext();
+	ext.execute_with(|| {
+		register_member_service_of(ServiceType::WssRPC, Some(key));
+
+		// Nothing is submitted until the WebSocket prober has stored a result.
+		IBP::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+		assert_eq!(
+			StorageValueRef::persistent(WEBSOCKET_TARGETS_KEY).get::<Vec<WebSocketTarget>>(),
+			Ok(Some(vec![WebSocketTarget {
+				member_service_id: 0,
+				url: b"wss://rpc.helikon.io:443/polkadot".to_vec(),
+				rpc: true,
+				required_methods: vec![],
+				archive_block_hash: None,
+			}])),
+		);
+
+		StorageValueRef::persistent(&websocket_result_key(0)).set(&WebSocketProbeResult {
+			timestamp: 1_700_000_000_000,
+			result: HealthCheckResult::Ok,
+			response_time_ms: 42,
+			peer_count: Some(12),
+			is_syncing: Some(false),
+			genesis_hash: Some(genesis(POLKADOT_GENESIS)),
+		});
+		IBP::offchain_worker(2);
+		let (payload, signature) = submitted_health_check(&pool_state);
+		assert_eq!(payload.health_check.response_time_ms, 42);
+		assert_eq!(payload.health_check.peer_count, Some(12));
+		assert_ok!(IBP::submit_health_check_unsigned(RuntimeOrigin::none(), payload, signature));
+		let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+		assert_eq!(history[0].result, HealthCheckResult::Ok);
+	});
+}
+
+#[test]
+fn ipfs_gateway_is_probed_over_https() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	offchain_state.write().expect_request(PendingRequest {
+		method: "GET".into(),
+		uri: format!("{}{}", RPC_URL, IPFS_PROBE_PATH),
+		response: Some(b"<html></html>".to_vec()),
+		sent: true,
+		..Default::default()
+	});
+	ext.execute_with(|| {
+		register_member_service_of(ServiceType::IpfsGateway, Some(key));
+		IBP::offchain_worker(1);
+		let (payload, _) = submitted_health_check(&pool_state);
+		assert_eq!(payload.health_check.result, HealthCheckResult::Ok);
+		assert_eq!(payload.health_check.genesis_hash, None);
+	});
+}
+
+#[test]
+fn migration_to_v3_sets_default_probe_params() {
+	new_test_ext().execute_with(|| {
+		let old_service = (
+			0u32,
+			ServiceType::ArchiveRPC,
+			bounded::<ConstU32<64>>(b"Polkadot archive"),
+			bounded::<ConstU32<32>>(b"/polkadot"),
+			Some(0u32),
+		);
+		let key = crate::Services::<Test>::hashed_key_for(0);
+		frame_support::storage::unhashed::put(&key, &old_service);
+		StorageVersion::new(2).put::<IBP>();
+		crate::migrations::v3::MigrateToV3::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<IBP>(), 3);
+		let (id, ty, _, _, network_id, probe) = frame_support::storage::unhashed::get::<(
+			u32,
+			ServiceType,
+			BoundedVec<u8, ConstU32<64>>,
+			BoundedVec<u8, ConstU32<32>>,
+			Option<u32>,
+			ProbeParams,
+		)>(&key)
+		.unwrap();
+		assert_eq!((id, ty, network_id), (0, ServiceType::ArchiveRPC, Some(0)));
+		assert_eq!(probe, ProbeParams::default_for(&ServiceType::ArchiveRPC));
+	});
+}
+
+const BOOTNODE: &[u8] =
+	b"/dns/boot.helikon.io/tcp/30333/wss/p2p/12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+
+#[test]
+fn offchain_worker_submits_bootnode_probe_results() {
+	let (mut ext, _, pool_state, key) = offchain_test_ext();
+	ext.execute_with(|| {
+		assert_ok!(IBP::register_network(
+			RuntimeOrigin::root(),
+			bounded(b"Polkadot"),
+			genesis(POLKADOT_GENESIS),
+			None,
+			None,
+			0,
+			bounded(b"DOT"),
+		));
+		assert_ok!(IBP::register_service(
+			RuntimeOrigin::root(),
+			ServiceType::BootNode,
+			bounded(b"Polkadot bootnode"),
+			bounded(b""),
+			0,
+		));
+		add_member(member(), b"Helikon");
+		assert_ok!(IBP::register_member_service(
+			RuntimeOrigin::signed(member()),
+			0,
+			bounded(b"helikon-polkadot-boot"),
+			bounded(b"boot.helikon.io"),
+			30333,
+		));
+		assert_ok!(IBP::set_verification_status(
+			RuntimeOrigin::root(),
+			0,
+			VerificationStatus::Verified,
+		));
+		assert_ok!(IBP::register_monitor(
+			RuntimeOrigin::signed(member()),
+			monitor(),
+			bounded(b"monitor"),
+		));
+		assert_ok!(IBP::set_monitor_key(
+			RuntimeOrigin::signed(member()),
+			monitor(),
+			MultiSigner::from(key),
+		));
+		assert_noop!(
+			IBP::set_bootnode(
+				RuntimeOrigin::signed(member()),
+				0,
+				bounded(b"/dns/boot.helikon.io/tcp/30333"),
+			),
+			Error::<Test>::InvalidMultiaddr,
+		);
+		assert_ok!(IBP::set_bootnode(RuntimeOrigin::signed(member()), 0, bounded(BOOTNODE)));
+
+		// Nothing is submitted until the bootnode prober has stored a result.
+		IBP::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+		assert_eq!(
+			StorageValueRef::persistent(BOOTNODE_TARGETS_KEY).get::<Vec<BootNodeTarget>>(),
+			Ok(Some(vec![BootNodeTarget { member_service_id: 0, multiaddr: BOOTNODE.to_vec() }])),
+		);
+
+		StorageValueRef::persistent(&bootnode_result_key(0)).set(&BootNodeProbeResult {
+			timestamp: 1_700_000_000_000,
+			result: HealthCheckResult::Ok,
+			response_time_ms: 42,
+		});
+		IBP::offchain_worker(2);
+		let (payload, _) = submitted_health_check(&pool_state);
+		assert_eq!(payload.health_check.member_service_id, 0);
+		assert_eq!(payload.health_check.result, HealthCheckResult::Ok);
+		assert_eq!(payload.health_check.response_time_ms, 42);
+		let result = StorageValueRef::persistent(&bootnode_result_key(0));
+		assert_eq!(result.get::<BootNodeProbeResult>(), Ok(None));
+	});
+}
+
+const BLOCK_1_HASH: &str = "0xc0096358534ec8d21d01d34b836eed476a1c343f8724fa2153dc0725ad797a90";
+
+/// Register the member service of `register_member_service` as an archive RPC service of a network
+/// whose archive block is block 1.
+fn register_archive_member_service(key: sr25519::Public) {
+	register_member_service(Some(key));
+	assert_ok!(IBP::set_probe_params(
+		RuntimeOrigin::root(),
+		0,
+		ProbeParams {
+			protocol: Protocol::Https,
+			required_methods: Default::default(),
+			archive: true
+		},
+	));
+	assert_ok!(IBP::set_archive_block(RuntimeOrigin::root(), 0, Some(genesis(BLOCK_1_HASH))));
+}
+
+#[test]
+fn wss_archive_endpoints_are_published_with_their_archive_block() {
+	let (mut ext, _, _, key) = offchain_test_ext();
+	ext.execute_with(|| {
+		register_member_service_of(ServiceType::WssRPC, Some(key));
+		assert_ok!(IBP::set_probe_params(
+			RuntimeOrigin::root(),
+			0,
+			ProbeParams {
+				protocol: Protocol::Wss,
+				required_methods: Default::default(),
+				archive: true
+			},
+		));
+		assert_ok!(IBP::set_archive_block(RuntimeOrigin::root(), 0, Some(genesis(BLOCK_1_HASH))));
+		IBP::offchain_worker(1);
+		let targets = StorageValueRef::persistent(WEBSOCKET_TARGETS_KEY)
+			.get::<Vec<WebSocketTarget>>()
+			.unwrap()
+			.unwrap();
+		assert_eq!(targets[0].archive_block_hash, Some(genesis(BLOCK_1_HASH)));
+	});
+}
+
+#[test]
+fn archive_probe_accepts_historical_state() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_health(&offchain_state, r#"{"peers":12,"isSyncing":false}"#);
+	expect_rpc_request(
+		&offchain_state,
+		&state_get_system_number_request(genesis(BLOCK_1_HASH)),
+		br#"{"jsonrpc":"2.0","result":"0x01000000","id":1}"#,
+	);
+	expect_chain_queries(&offchain_state, POLKADOT_GENESIS);
+	ext.execute_with(|| {
+		register_archive_member_service(key);
+		IBP::offchain_worker(1);
+		let (payload, _) = submitted_health_check(&pool_state);
+		assert_eq!(payload.health_check.result, HealthCheckResult::Ok);
+	});
+}
+
+#[test]
+fn archive_probe_reports_pruned_state() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	expect_health(&offchain_state, r#"{"peers":12,"isSyncing":false}"#);
+	expect_rpc_request(
+		&offchain_state,
+		&state_get_system_number_request(genesis(BLOCK_1_HASH)),
+		br#"{"jsonrpc":"2.0","error":{"code":4003,"message":"Client error: State already discarded"},"id":1}"#,
+	);
+	ext.execute_with(|| {
+		register_archive_member_service(key);
+		IBP::offchain_worker(1);
+		let (payload, signature) = submitted_health_check(&pool_state);
+		assert_eq!(
+			payload.health_check.result,
+			HealthCheckResult::Failed(FailureCode::StateUnavailable),
+		);
+		assert_ok!(IBP::submit_health_check_unsigned(RuntimeOrigin::none(), payload, signature));
+		let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+		assert_eq!(history[0].result, HealthCheckResult::Failed(FailureCode::StateUnavailable));
+	});
+}
+
+#[test]
+fn state_get_storage_request_queries_system_number() {
+	assert_eq!(
+		String::from_utf8(state_get_system_number_request(genesis(BLOCK_1_HASH))).unwrap(),
+		format!(
+			r#"{{"id":1,"jsonrpc":"2.0","method":"state_getStorage","params":["{}","{}"]}}"#,
+			"0x26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac", BLOCK_1_HASH,
+		),
+	);
+}
+
+/// Register the member service of `register_member_service` without verifying it, and a monitor of
+/// another member, bound to `key`.
+fn register_unverified_member_service(key: Option<sr25519::Public>) {
+	assert_ok!(IBP::register_network(
+		RuntimeOrigin::root(),
+		bounded(b"Polkadot"),
+		genesis(POLKADOT_GENESIS),
+		None,
+		None,
+		0,
+		bounded(b"DOT"),
+	));
+	assert_ok!(IBP::register_service(
+		RuntimeOrigin::root(),
+		ServiceType::RPC,
+		bounded(b"Polkadot RPC"),
+		bounded(b"/polkadot"),
+		0,
+	));
+	add_member(member(), b"Helikon");
+	assert_ok!(IBP::register_member_service(
+		RuntimeOrigin::signed(member()),
+		0,
+		bounded(b"helikon-polkadot-rpc"),
+		bounded(b"rpc.helikon.io"),
+		443,
+	));
+	add_member(other_member(), b"Other");
+	assert_ok!(IBP::register_monitor(
+		RuntimeOrigin::signed(other_member()),
+		monitor(),
+		bounded(b"monitor"),
+	));
+	if let Some(key) = key {
+		assert_ok!(IBP::set_monitor_key(
+			RuntimeOrigin::signed(other_member()),
+			monitor(),
+			MultiSigner::from(key),
+		));
+	}
+}
+
+fn other_member() -> AccountId32 {
+	AccountId32::new([3; 32])
+}
+
+#[test]
+fn member_service_is_verified_by_attestations_of_other_members() {
+	new_test_ext().execute_with(|| {
+		register_unverified_member_service(None);
+		let token = DomainChallenges::<Test>::get(0).unwrap().token;
+		let health_check = HealthCheck {
+			member_service_id: 0,
+			timestamp: 1_700_000_000_000,
+			result: HealthCheckResult::Ok,
+			response_time_ms: 42,
+			best_block: None,
+			finalized_block: None,
+			peer_count: None,
+			is_syncing: None,
+			genesis_hash: None,
+			certificate: None,
+		};
+		assert_noop!(
+			IBP::submit_health_check(RuntimeOrigin::signed(monitor()), health_check.clone()),
+			Error::<Test>::MemberServiceNotVerified,
+		);
+
+		let own_monitor = AccountId32::new([4; 32]);
+		assert_ok!(IBP::register_monitor(
+			RuntimeOrigin::signed(member()),
+			own_monitor.clone(),
+			bounded(b"own monitor"),
+		));
+		assert_noop!(
+			IBP::attest_domain(RuntimeOrigin::signed(own_monitor), 0, token),
+			Error::<Test>::SelfAttestation,
+		);
+		assert_noop!(
+			IBP::attest_domain(RuntimeOrigin::signed(monitor()), 0, H256::repeat_byte(1)),
+			Error::<Test>::InvalidChallengeToken,
+		);
+		assert_ok!(IBP::attest_domain(RuntimeOrigin::signed(monitor()), 0, token));
+		assert!(IBP::is_verified(0));
+		assert_eq!(DomainChallenges::<Test>::get(0), None);
+		assert_ok!(IBP::submit_health_check(RuntimeOrigin::signed(monitor()), health_check));
+	});
+}
+
+#[test]
+fn offchain_worker_attests_challenge_published_in_dns() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	ext.execute_with(|| {
+		register_unverified_member_service(Some(key));
+		let token = DomainChallenges::<Test>::get(0).unwrap().token;
+		let mut state = offchain_state.write();
+		state.expect_request(PendingRequest {
+			method: "GET".into(),
+			uri: "https://rpc.helikon.io/.well-known/ibp-challenge".into(),
+			headers: vec![("Accept".into(), "text/plain".into())],
+			response: Some(b"Not Found".to_vec()),
+			sent: true,
+			..Default::default()
+		});
+		state.expect_request(PendingRequest {
+			method: "GET".into(),
+			uri: format!(
+				"{}?name=_ibp-challenge.rpc.helikon.io&type=TXT",
+				crate::offchain::DNS_OVER_HTTPS_URL
+			),
+			headers: vec![("Accept".into(), "application/dns-json".into())],
+			response: Some(
+				format!(
+					r#"{{"Status":0,"Answer":[{{"name":"_ibp-challenge.rpc.helikon.io","type":16,"data":"\"{:?}\""}}]}}"#,
+					token
+				)
+				.into_bytes(),
+			),
+			sent: true,
+			..Default::default()
+		});
+		drop(state);
+		IBP::offchain_worker(1);
+		let tx = pool_state.write().transactions.pop().unwrap();
+		let tx = Extrinsic::decode(&mut &*tx).unwrap();
+		let (payload, signature): (DomainAttestationPayload<MultiSigner, u64>, _) = match tx.call {
+			RuntimeCall::IBP(Call::submit_domain_attestation_unsigned { payload, signature }) =>
+				(payload, signature),
+			call => panic!("unexpected call: {:?}", call),
+		};
+		assert_eq!(payload.token, token);
+		let call = Call::submit_domain_attestation_unsigned {
+			payload: payload.clone(),
+			signature: signature.clone(),
+		};
+		assert_ok!(IBP::validate_unsigned(TransactionSource::Local, &call));
+		assert_ok!(IBP::submit_domain_attestation_unsigned(
+			RuntimeOrigin::none(),
+			payload,
+			signature
+		));
+		assert!(IBP::is_verified(0));
+	});
+}
+
+#[test]
+fn offchain_worker_queries_the_configured_dns_resolver() {
+	let (mut ext, offchain_state, pool_state, key) = offchain_test_ext();
+	ext.execute_with(|| {
+		register_unverified_member_service(Some(key));
+		sp_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			DNS_OVER_HTTPS_URL_KEY,
+			b"https://dns.quad9.net:5053/dns-query",
+		);
+		let mut state = offchain_state.write();
+		state.expect_request(PendingRequest {
+			method: "GET".into(),
+			uri: "https://rpc.helikon.io/.well-known/ibp-challenge".into(),
+			headers: vec![("Accept".into(), "text/plain".into())],
+			response: Some(b"Not Found".to_vec()),
+			sent: true,
+			..Default::default()
+		});
+		state.expect_request(PendingRequest {
+			method: "GET".into(),
+			uri: "https://dns.quad9.net:5053/dns-query?name=_ibp-challenge.rpc.helikon.io&type=TXT"
+				.into(),
+			headers: vec![("Accept".into(), "application/dns-json".into())],
+			response: Some(br#"{"Status":3}"#.to_vec()),
+			sent: true,
+			..Default::default()
+		});
+		drop(state);
+		IBP::offchain_worker(1);
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn checks_in_maintenance_windows_are_recorded_but_not_rolled_up() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		let schedule = |start: u64, end: u64| {
+			IBP::schedule_maintenance(
+				RuntimeOrigin::signed(member()),
+				0,
+				start,
+				end,
+				bounded(b"client upgrade"),
+			)
+		};
+		assert_noop!(schedule(5, 15), Error::<Test>::MaintenanceNoticeTooShort);
+		assert_noop!(schedule(11, 40), Error::<Test>::InvalidMaintenanceWindow);
+		assert_ok!(schedule(11, 31));
+		assert_ok!(schedule(50, 60));
+		assert_noop!(schedule(70, 80), Error::<Test>::TooManyMaintenances);
+		assert_eq!(IBP::maintenance_windows(0).len(), 2);
+
+		let health_check = HealthCheck {
+			member_service_id: 0,
+			timestamp: 1_700_000_000_000,
+			result: HealthCheckResult::Failed(FailureCode::Connection),
+			response_time_ms: 0,
+			best_block: None,
+			finalized_block: None,
+			peer_count: None,
+			is_syncing: None,
+			genesis_hash: None,
+			certificate: None,
+		};
+		System::set_block_number(15);
+		assert!(IBP::in_maintenance(0, 15));
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			health_check.clone()
+		));
+		assert_eq!(HealthChecks::<Test>::get(0, monitor()).unwrap().len(), 1);
+		assert_eq!(crate::EraRollups::<Test>::get(0, 0).checks, 0);
+
+		System::set_block_number(31);
+		assert_ok!(IBP::submit_health_check(RuntimeOrigin::signed(monitor()), health_check));
+		assert_eq!(crate::EraRollups::<Test>::get(0, 0).checks, 1);
+		assert_eq!(IBP::maintenance_windows(0).len(), 1);
+	});
+}
+
+#[test]
+fn consecutive_failures_open_an_incident_that_recovery_closes() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		let health_check = |result| HealthCheck {
+			member_service_id: 0,
+			timestamp: 1_700_000_000_000,
+			result,
+			response_time_ms: 0,
+			best_block: None,
+			finalized_block: None,
+			peer_count: None,
+			is_syncing: None,
+			genesis_hash: Some(genesis(POLKADOT_GENESIS)),
+			certificate: None,
+		};
+		let failures =
+			[FailureCode::Syncing, FailureCode::WrongChain, FailureCode::Timeout, FailureCode::Dns];
+		for (i, code) in failures.into_iter().enumerate() {
+			System::set_block_number(1 + 10 * i as u64);
+			assert_ok!(IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				health_check(HealthCheckResult::Failed(code)),
+			));
+			assert_eq!(crate::OpenIncidents::<Test>::get(0).is_some(), i >= 2);
+		}
+		System::assert_has_event(
+			crate::Event::IncidentOpened {
+				id: 0,
+				member_service_id: 0,
+				peak_failure: FailureCode::WrongChain,
+			}
+			.into(),
+		);
+
+		System::set_block_number(41);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			health_check(HealthCheckResult::Ok),
+		));
+		let incident = crate::Incidents::<Test>::get(0).unwrap();
+		assert_eq!(incident.streak.failures, 4);
+		assert_eq!(incident.streak.started_at, 1);
+		assert_eq!(incident.streak.monitors.into_inner(), vec![monitor()]);
+		assert_eq!(incident.ended_at, Some(41));
+		assert_eq!(crate::OpenIncidents::<Test>::get(0), None);
+
+		assert_noop!(
+			IBP::set_post_mortem(RuntimeOrigin::signed(monitor()), 0, bounded(b"https://x")),
+			Error::<Test>::MemberNotFound,
+		);
+		assert_ok!(IBP::set_post_mortem(
+			RuntimeOrigin::signed(member()),
+			0,
+			bounded(b"https://status.helikon.io/incidents/1"),
+		));
+		assert!(crate::Incidents::<Test>::get(0).unwrap().post_mortem.is_some());
+	});
+}
+
+#[test]
+fn incidents_open_past_the_history_are_removed_once_closed() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		for address in [b"rpc2.helikon.io", b"rpc3.helikon.io"] {
+			assert_ok!(IBP::register_member_service(
+				RuntimeOrigin::signed(member()),
+				0,
+				bounded(b"helikon-polkadot-rpc"),
+				bounded(address),
+				443,
+			));
+		}
+		for member_service_id in [1, 2] {
+			assert_ok!(IBP::set_verification_status(
+				RuntimeOrigin::root(),
+				member_service_id,
+				VerificationStatus::Verified,
+			));
+		}
+		for block in [1, 11, 21] {
+			System::set_block_number(block);
+			for member_service_id in [0, 1, 2] {
+				assert_ok!(IBP::submit_health_check(
+					RuntimeOrigin::signed(monitor()),
+					HealthCheck { member_service_id, ..failed_health_check(1_700_000_000_000) },
+				));
+			}
+		}
+		// Incident 0 is still open when incident 2 pushes it out of the history.
+		assert_eq!(crate::IncidentCount::<Test>::get(), Some(3));
+		assert!(crate::Incidents::<Test>::get(0).is_some());
+
+		System::set_block_number(31);
+		for member_service_id in [0, 1] {
+			assert_ok!(IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				up_health_check(member_service_id, 100, 98),
+			));
+		}
+		System::assert_has_event(
+			crate::Event::IncidentClosed {
+				id: 0,
+				member_service_id: 0,
+				started_at: 1,
+				ended_at: 31,
+			}
+			.into(),
+		);
+		assert_eq!(crate::Incidents::<Test>::get(0), None);
+		assert_eq!(crate::Incidents::<Test>::get(1).unwrap().ended_at, Some(31));
+		assert!(crate::Incidents::<Test>::get(2).is_some());
+	});
+}
+
+/// A failed health check of member service 0 with the given timestamp.
+fn failed_health_check(timestamp: u64) -> HealthCheck {
+	HealthCheck {
+		member_service_id: 0,
+		timestamp,
+		result: HealthCheckResult::Failed(FailureCode::Timeout),
+		response_time_ms: 0,
+		best_block: None,
+		finalized_block: None,
+		peer_count: None,
+		is_syncing: None,
+		genesis_hash: None,
+		certificate: None,
+	}
+}
+
+/// An up health check of a member service of `register_member_service` reporting the given
+/// heights.
+fn up_health_check(member_service_id: u32, best_block: u32, finalized_block: u32) -> HealthCheck {
+	HealthCheck {
+		member_service_id,
+		timestamp: 0,
+		result: HealthCheckResult::Ok,
+		response_time_ms: 100,
+		best_block: Some(best_block),
+		finalized_block: Some(finalized_block),
+		peer_count: Some(12),
+		is_syncing: Some(false),
+		genesis_hash: Some(genesis(POLKADOT_GENESIS)),
+		certificate: None,
+	}
+}
+
+#[test]
+fn heights_are_compared_with_the_median_once_their_window_closes() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		for address in [b"rpc2.helikon.io", b"rpc3.helikon.io"] {
+			assert_ok!(IBP::register_member_service(
+				RuntimeOrigin::signed(member()),
+				0,
+				bounded(b"helikon-polkadot-rpc"),
+				bounded(address),
+				443,
+			));
+		}
+		for member_service_id in [1, 2] {
+			assert_ok!(IBP::set_verification_status(
+				RuntimeOrigin::root(),
+				member_service_id,
+				VerificationStatus::Verified,
+			));
+		}
+		for (member_service_id, best_block, finalized_block) in
+			[(0, 100, 98), (1, 101, 99), (2, 90, 98)]
+		{
+			assert_ok!(IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				up_health_check(member_service_id, best_block, finalized_block),
+			));
+		}
+		// The window is evaluated once a check of the next window arrives.
+		assert_eq!(crate::EraRollups::<Test>::get(0, 2).height_windows, 0);
+		System::set_block_number(11);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			up_health_check(0, 110, 108),
+		));
+		System::assert_has_event(
+			crate::Event::ServiceLagging {
+				service_id: 0,
+				member_service_id: 2,
+				best_lag: 10,
+				finalized_lag: 0,
+			}
+			.into(),
+		);
+		let lagging = crate::EraRollups::<Test>::get(0, 2);
+		assert_eq!(lagging.height_windows, 1);
+		assert_eq!(lagging.lagging_windows, 1);
+		assert_eq!((lagging.total_lag, lagging.max_lag), (10, 10));
+		// The upper median of 90, 100 and 101 is 100, which member service 1 leads.
+		let leading = crate::EraRollups::<Test>::get(0, 1);
+		assert_eq!(leading.height_windows, 1);
+		assert_eq!((leading.lagging_windows, leading.total_lag), (0, 0));
+		assert_eq!(crate::EraRollups::<Test>::get(0, 0).total_lag, 0);
+
+		// Rollups are dropped `EraHistoryDepth` eras later.
+		IBP::on_initialize(300);
+		assert_eq!(crate::EraRollups::<Test>::get(0, 2).height_windows, 1);
+		IBP::on_initialize(400);
+		assert_eq!(crate::EraRollups::<Test>::get(0, 2).height_windows, 0);
+	});
+}
+
+#[test]
+fn height_windows_close_when_the_next_window_starts() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		for address in [b"rpc2.helikon.io", b"rpc3.helikon.io"] {
+			assert_ok!(IBP::register_member_service(
+				RuntimeOrigin::signed(member()),
+				0,
+				bounded(b"helikon-polkadot-rpc"),
+				bounded(address),
+				443,
+			));
+		}
+		for member_service_id in [1, 2] {
+			assert_ok!(IBP::set_verification_status(
+				RuntimeOrigin::root(),
+				member_service_id,
+				VerificationStatus::Verified,
+			));
+		}
+		for (member_service_id, best_block, finalized_block) in
+			[(0, 100, 98), (1, 101, 99), (2, 90, 98)]
+		{
+			assert_ok!(IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				up_health_check(member_service_id, best_block, finalized_block),
+			));
+		}
+
+		// Nothing reports in the next window, which still closes the previous one.
+		IBP::on_initialize(5);
+		assert!(crate::HeightWindows::<Test>::get(0).is_some());
+		IBP::on_initialize(10);
+		assert!(crate::HeightWindows::<Test>::get(0).is_none());
+		System::assert_has_event(
+			crate::Event::ServiceLagging {
+				service_id: 0,
+				member_service_id: 2,
+				best_lag: 10,
+				finalized_lag: 0,
+			}
+			.into(),
+		);
+		assert_eq!(crate::EraRollups::<Test>::get(0, 2).lagging_windows, 1);
+		assert_eq!(crate::EraRollups::<Test>::get(0, 1).height_windows, 1);
+	});
+}
+
+#[test]
+fn upheld_dispute_removes_reports_and_reverses_rewards() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		Balances::make_free_balance_be(&member(), 1_000);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			failed_health_check(1_700_000_000_000),
+		));
+		assert_eq!(Balances::free_balance(monitor()), 100);
+		assert_eq!(crate::EraRollups::<Test>::get(0, 0).checks, 1);
+
+		let target = crate::DisputeTarget::Reports {
+			monitor: monitor(),
+			timestamps: vec![1_700_000_000_000].try_into().unwrap(),
+		};
+		assert_noop!(
+			IBP::dispute_health_check(
+				RuntimeOrigin::signed(member()),
+				0,
+				crate::DisputeTarget::Reports {
+					monitor: monitor(),
+					timestamps: vec![1].try_into().unwrap(),
+				},
+			),
+			Error::<Test>::ReportNotFound,
+		);
+		assert_ok!(IBP::dispute_health_check(RuntimeOrigin::signed(member()), 0, target.clone()));
+		assert_eq!(Balances::reserved_balance(member()), 150);
+		assert_noop!(
+			IBP::dispute_health_check(RuntimeOrigin::signed(member()), 0, target),
+			Error::<Test>::ReportAlreadyDisputed,
+		);
+
+		assert_ok!(IBP::resolve_dispute(RuntimeOrigin::root(), 0, true));
+		assert_eq!(Balances::reserved_balance(member()), 100);
+		assert_eq!(Balances::free_balance(member()), 1_000);
+		assert_eq!(Balances::free_balance(monitor()), 0);
+		assert_eq!(Balances::free_balance(IBP::reward_pot()), 100);
+		assert_eq!(crate::EraRollups::<Test>::get(0, 0).checks, 0);
+		assert_eq!(crate::MonitorStrikes::<Test>::get(monitor()), 1);
+		assert!(crate::FailedReports::<Test>::get(0, monitor()).is_empty());
+	});
+}
+
+#[test]
+fn dispute_rejected_by_member_vote_slashes_bond() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		Balances::make_free_balance_be(&member(), 1_000);
+		let voter = AccountId32::new([5; 32]);
+		add_member(other_member(), b"Other");
+		add_member(voter.clone(), b"Voter");
+		for (i, timestamp) in [1_700_000_000_000, 1_700_000_600_000].into_iter().enumerate() {
+			System::set_block_number(1 + 10 * i as u64);
+			assert_ok!(IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				failed_health_check(timestamp),
+			));
+		}
+		assert_ok!(IBP::dispute_health_check(
+			RuntimeOrigin::signed(member()),
+			0,
+			crate::DisputeTarget::Reports {
+				monitor: monitor(),
+				timestamps: vec![1_700_000_000_000, 1_700_000_600_000].try_into().unwrap(),
+			},
+		));
+		assert_noop!(
+			IBP::vote_on_dispute(RuntimeOrigin::signed(member()), 0, true),
+			Error::<Test>::OwnDispute,
+		);
+		assert_ok!(IBP::vote_on_dispute(RuntimeOrigin::signed(other_member()), 0, false));
+		assert_noop!(
+			IBP::vote_on_dispute(RuntimeOrigin::signed(other_member()), 0, true),
+			Error::<Test>::AlreadyVoted,
+		);
+		assert_ok!(IBP::vote_on_dispute(RuntimeOrigin::signed(voter), 0, false));
+		assert_eq!(crate::Disputes::<Test>::get(0), None);
+		assert_eq!(Balances::reserved_balance(member()), 100);
+		assert_eq!(Balances::free_balance(member()), 950);
+		assert_eq!(Balances::free_balance(IBP::reward_pot()), 50);
+		assert_eq!(Balances::free_balance(monitor()), 200);
+		assert_eq!(crate::EraRollups::<Test>::get(0, 0).checks, 2);
+	});
+}
+
+#[test]
+fn incident_disputes_cover_the_reports_within_the_incident() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		Balances::make_free_balance_be(&member(), 1_000);
+		let timestamp = |block: u64| 1_700_000_000_000 + block * 6_000;
+		let check = |block: u64, health_check: HealthCheck| {
+			System::set_block_number(block);
+			assert_ok!(IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				HealthCheck { timestamp: timestamp(block), ..health_check },
+			));
+		};
+		// A failure before the incident, the incident from 21 to 51 and a failure after it.
+		check(1, failed_health_check(0));
+		check(11, up_health_check(0, 100, 98));
+		for block in [21, 31, 41] {
+			check(block, failed_health_check(0));
+		}
+		check(51, up_health_check(0, 100, 98));
+		check(61, failed_health_check(0));
+		assert_eq!(crate::Incidents::<Test>::get(0).unwrap().streak.started_at, 21);
+
+		assert_ok!(IBP::dispute_health_check(
+			RuntimeOrigin::signed(member()),
+			0,
+			crate::DisputeTarget::Incident(0),
+		));
+		let dispute = crate::Disputes::<Test>::get(0).unwrap();
+		assert_eq!(
+			dispute.reports.into_inner(),
+			[21, 31, 41].map(|block| (monitor(), timestamp(block))).to_vec(),
+		);
+		let disputed: Vec<_> = crate::FailedReports::<Test>::get(0, monitor())
+			.into_iter()
+			.map(|report| (report.block_number, report.disputed))
+			.collect();
+		assert_eq!(disputed, vec![(1, false), (21, true), (31, true), (41, true), (61, false)]);
+		assert_noop!(
+			IBP::dispute_health_check(
+				RuntimeOrigin::signed(member()),
+				0,
+				crate::DisputeTarget::Incident(0),
+			),
+			Error::<Test>::NoReportsToDispute,
+		);
+	});
+}
+
+#[test]
+fn unresolved_disputes_expire_and_refund_the_bond() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		Balances::make_free_balance_be(&member(), 1_000);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			failed_health_check(1_700_000_000_000),
+		));
+		let target = crate::DisputeTarget::Reports {
+			monitor: monitor(),
+			timestamps: vec![1_700_000_000_000].try_into().unwrap(),
+		};
+		assert_ok!(IBP::dispute_health_check(RuntimeOrigin::signed(member()), 0, target.clone()));
+		assert_eq!(Balances::reserved_balance(member()), 150);
+		assert_eq!(crate::DisputeExpiries::<Test>::get(51).into_inner(), vec![0]);
+
+		IBP::on_initialize(50);
+		assert!(crate::Disputes::<Test>::get(0).is_some());
+		IBP::on_initialize(51);
+		System::assert_last_event(crate::Event::DisputeExpired { id: 0 }.into());
+		assert_eq!(crate::Disputes::<Test>::get(0), None);
+		assert!(crate::DisputeExpiries::<Test>::get(51).is_empty());
+		assert_eq!(Balances::reserved_balance(member()), 100);
+		assert_eq!(Balances::free_balance(member()), 1_000);
+		// The reports stand and can't be disputed again.
+		assert_eq!(crate::EraRollups::<Test>::get(0, 0).checks, 1);
+		assert_noop!(
+			IBP::dispute_health_check(RuntimeOrigin::signed(member()), 0, target),
+			Error::<Test>::ReportAlreadyDisputed,
+		);
+	});
+}
+
+#[test]
+fn disputes_fail_once_their_ids_run_out() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		Balances::make_free_balance_be(&member(), 1_000);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			failed_health_check(1_700_000_000_000),
+		));
+		crate::DisputeCount::<Test>::put(u32::MAX);
+		let target = crate::DisputeTarget::Reports {
+			monitor: monitor(),
+			timestamps: vec![1_700_000_000_000].try_into().unwrap(),
+		};
+		assert_noop!(
+			IBP::dispute_health_check(RuntimeOrigin::signed(member()), 0, target),
+			Error::<Test>::DisputeIdOverflow,
+		);
+		assert_eq!(crate::Disputes::<Test>::get(u32::MAX), None);
+		assert_eq!(Balances::reserved_balance(member()), 100);
+	});
+}
+
+#[test]
+fn migration_to_v10_schedules_open_disputes_to_expire() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		Balances::make_free_balance_be(&member(), 1_000);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			failed_health_check(1_700_000_000_000),
+		));
+		assert_ok!(IBP::dispute_health_check(
+			RuntimeOrigin::signed(member()),
+			0,
+			crate::DisputeTarget::Reports {
+				monitor: monitor(),
+				timestamps: vec![1_700_000_000_000].try_into().unwrap(),
+			},
+		));
+		let _ = crate::DisputeExpiries::<Test>::clear(u32::MAX, None);
+		StorageVersion::new(9).put::<IBP>();
+		System::set_block_number(20);
+		crate::migrations::v10::MigrateToV10::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<IBP>(), 10);
+		assert_eq!(crate::DisputeExpiries::<Test>::get(70).into_inner(), vec![0]);
+	});
+}
+
+#[test]
+fn deregistering_member_services_releases_their_deposit() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		add_member(other_member(), b"Other");
+		assert_eq!(Balances::reserved_balance(member()), 100);
+		assert_eq!(crate::ActiveMemberServices::<Test>::get(), 1);
+		assert_noop!(
+			IBP::deregister_member_service(RuntimeOrigin::signed(other_member()), 0),
+			Error::<Test>::NotMemberServiceOwner,
+		);
+		crate::UnappliedSlashes::<Test>::insert(
+			3,
+			BoundedVec::truncate_from(vec![crate::UnappliedSlash {
+				member_service_id: 0,
+				amount: 10,
+			}]),
+		);
+		assert_noop!(
+			IBP::deregister_member_service(RuntimeOrigin::signed(member()), 0),
+			Error::<Test>::SlashPending,
+		);
+		assert_ok!(IBP::cancel_deferred_slash(RuntimeOrigin::root(), 3, vec![0]));
+
+		assert_ok!(IBP::deregister_member_service(RuntimeOrigin::signed(member()), 0));
+		System::assert_last_event(
+			crate::Event::MemberServiceDeregistered { member_service_id: 0 }.into(),
+		);
+		assert_eq!(Balances::reserved_balance(member()), 0);
+		assert_eq!(Balances::free_balance(member()), 1_000);
+		assert_eq!(crate::MemberServices::<Test>::get(0), None);
+		assert_eq!(crate::MemberServiceDeposits::<Test>::get(0), None);
+		assert_eq!(crate::ActiveMemberServices::<Test>::get(), 0);
+		assert!(!IBP::is_verified(0));
+		assert_noop!(
+			IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				failed_health_check(1_700_000_000_000),
+			),
+			Error::<Test>::MemberServiceNotFound,
+		);
+	});
+}
+
+#[test]
+fn member_services_are_bounded() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		let register = || {
+			IBP::register_member_service(
+				RuntimeOrigin::signed(member()),
+				0,
+				bounded(b"helikon-polkadot-rpc"),
+				bounded(b"rpc2.helikon.io"),
+				443,
+			)
+		};
+		for _ in 1..4 {
+			assert_ok!(register());
+		}
+		assert_noop!(register(), Error::<Test>::TooManyMemberServices);
+		assert_ok!(IBP::deregister_member_service(RuntimeOrigin::signed(member()), 1));
+		assert_ok!(register());
+		assert_eq!(crate::ActiveMemberServices::<Test>::get(), 4);
+	});
+}
+
+#[test]
+fn migration_to_v11_counts_member_services_and_reserves_missing_deposits() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		// A member service registered before deposits, by a member that can't afford one.
+		add_member(other_member(), b"Other");
+		assert_ok!(IBP::register_member_service(
+			RuntimeOrigin::signed(other_member()),
+			0,
+			bounded(b"other-polkadot-rpc"),
+			bounded(b"rpc.other.io"),
+			443,
+		));
+		for (account, member_service_id) in [(member(), 0), (other_member(), 1)] {
+			crate::MemberServiceDeposits::<Test>::remove(member_service_id);
+			Balances::unreserve(&account, 100);
+		}
+		Balances::make_free_balance_be(&other_member(), 50);
+		crate::ActiveMemberServices::<Test>::kill();
+		StorageVersion::new(10).put::<IBP>();
+
+		crate::migrations::v11::MigrateToV11::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<IBP>(), 11);
+		assert_eq!(crate::ActiveMemberServices::<Test>::get(), 2);
+		assert_eq!(Balances::reserved_balance(member()), 100);
+		assert_eq!(crate::MemberServiceDeposits::<Test>::get(0).unwrap().amount, 100);
+		assert_eq!(Balances::reserved_balance(other_member()), 0);
+		assert_eq!(crate::MemberServiceDeposits::<Test>::get(1).unwrap().amount, 0);
+	});
+}
+
+#[test]
+fn slo_breaches_escalate_from_warning_to_slash_and_suspension() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		assert_eq!(Balances::reserved_balance(member()), 100);
+		for era in 0..3 {
+			crate::EraRollups::<Test>::insert(
+				era,
+				0,
+				crate::EraRollup { checks: 10, up_checks: 9, ..Default::default() },
+			);
+		}
+
+		IBP::on_initialize(100);
+		assert_eq!(crate::BreachStreaks::<Test>::get(0), 1);
+		assert!(crate::UnappliedSlashes::<Test>::iter().next().is_none());
+
+		IBP::on_initialize(200);
+		assert_eq!(crate::UnappliedSlashes::<Test>::get(3).len(), 1);
+		assert_eq!(Balances::reserved_balance(member()), 100);
+
+		IBP::on_initialize(300);
+		assert_eq!(Balances::reserved_balance(member()), 90);
+		assert_eq!(Balances::free_balance(IBP::reward_pot()), 10);
+		assert!(crate::SuspendedMemberServices::<Test>::contains_key(0));
+		assert_noop!(
+			IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				failed_health_check(1_700_000_000_000),
+			),
+			Error::<Test>::MemberServiceSuspended,
+		);
+
+		assert_noop!(
+			IBP::cancel_deferred_slash(RuntimeOrigin::root(), 4, vec![1]),
+			Error::<Test>::InvalidSlashIndex,
+		);
+		assert_ok!(IBP::cancel_deferred_slash(RuntimeOrigin::root(), 4, vec![0]));
+		IBP::on_initialize(400);
+		assert_eq!(Balances::reserved_balance(member()), 90);
+
+		assert_ok!(IBP::resume_member_service(RuntimeOrigin::root(), 0));
+		assert_eq!(crate::BreachStreaks::<Test>::get(0), 0);
+		assert_noop!(
+			IBP::resume_member_service(RuntimeOrigin::root(), 0),
+			Error::<Test>::MemberServiceNotSuspended,
+		);
+	});
+}
+
+#[test]
+fn members_are_paid_by_uptime_latency_and_service_weight() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		assert_ok!(IBP::set_service_weight(RuntimeOrigin::root(), 0, 3));
+		crate::EraRollups::<Test>::insert(
+			0,
+			0,
+			crate::EraRollup {
+				checks: 10,
+				up_checks: 9,
+				total_response_time_ms: 9 * 400,
+				..Default::default()
+			},
+		);
+
+		IBP::on_initialize(100);
+		System::assert_has_event(
+			crate::Event::MemberPaid {
+				member: member(),
+				member_service_id: 0,
+				era: 0,
+				uptime: Perbill::from_percent(90),
+				latency_score: Perbill::from_percent(50),
+				service_weight: 3,
+				level: 0,
+				amount: 450,
+			}
+			.into(),
+		);
+		assert_eq!(crate::PendingRewards::<Test>::get(member()), 450);
+	});
+}
+
+#[test]
+fn lagging_windows_count_as_degraded_in_payouts_and_slo() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		crate::EraRollups::<Test>::insert(
+			0,
+			0,
+			crate::EraRollup {
+				checks: 10,
+				up_checks: 10,
+				total_response_time_ms: 10 * 100,
+				height_windows: 10,
+				lagging_windows: 5,
+				..Default::default()
+			},
+		);
+
+		IBP::on_initialize(100);
+		System::assert_has_event(
+			crate::Event::MemberPaid {
+				member: member(),
+				member_service_id: 0,
+				era: 0,
+				uptime: Perbill::from_percent(50),
+				latency_score: Perbill::one(),
+				service_weight: 1,
+				level: 0,
+				amount: 500,
+			}
+			.into(),
+		);
+		System::assert_has_event(
+			crate::Event::SloBreached {
+				member_service_id: 0,
+				era: 0,
+				uptime: Perbill::from_percent(50),
+				consecutive: 1,
+			}
+			.into(),
+		);
+		assert_eq!(crate::SlaStreaks::<Test>::get(member()).missed, 1);
+	});
+}
+
+#[test]
+fn members_claim_payouts_from_slashes_and_minted_funds() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		crate::EraRollups::<Test>::insert(
+			0,
+			0,
+			crate::EraRollup {
+				checks: 10,
+				up_checks: 10,
+				total_response_time_ms: 10 * 100,
+				..Default::default()
+			},
+		);
+		IBP::on_initialize(100);
+		assert_eq!(crate::PendingRewards::<Test>::get(member()), 1_000);
+		let issuance = Balances::total_issuance();
+
+		// Nothing was slashed into the pot, so the payout is minted.
+		assert_ok!(IBP::claim_rewards(RuntimeOrigin::signed(member())));
+		System::assert_last_event(
+			crate::Event::RewardsClaimed { member: member(), amount: 1_000 }.into(),
+		);
+		assert_eq!(Balances::free_balance(member()), 1_900);
+		assert_eq!(Balances::total_issuance(), issuance + 1_000);
+		assert_eq!(crate::PendingRewards::<Test>::get(member()), 0);
+		assert_noop!(
+			IBP::claim_rewards(RuntimeOrigin::signed(member())),
+			Error::<Test>::NoPendingRewards,
+		);
+
+		// Slashes in the pot are paid out first, keeping the pot alive.
+		Balances::make_free_balance_be(&IBP::reward_pot(), 300);
+		crate::PendingRewards::<Test>::insert(member(), 500);
+		assert_ok!(IBP::claim_rewards(RuntimeOrigin::signed(member())));
+		assert_eq!(Balances::free_balance(member()), 2_400);
+		assert_eq!(Balances::free_balance(IBP::reward_pot()), 1);
+	});
+}
+
+#[test]
+fn privileged_calls_require_their_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			IBP::register_service(
+				RuntimeOrigin::signed(member()),
+				ServiceType::RPC,
+				bounded(b"Polkadot RPC"),
+				bounded(b"/polkadot"),
+				0,
+			),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			IBP::cancel_deferred_slash(RuntimeOrigin::signed(member()), 0, vec![0]),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			IBP::resume_member_service(RuntimeOrigin::signed(member()), 0),
+			DispatchError::BadOrigin,
+		);
+	});
+}
+
+#[test]
+fn dispatch_as_root_dispatches_with_root_origin() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(IBP::register_network(
+			RuntimeOrigin::root(),
+			bounded(b"Polkadot"),
+			genesis(POLKADOT_GENESIS),
+			None,
+			None,
+			0,
+			bounded(b"DOT"),
+		));
+		let call = Box::new(RuntimeCall::IBP(Call::register_service {
+			ty: ServiceType::RPC,
+			name: bounded(b"Polkadot RPC"),
+			url_path: bounded(b"/polkadot"),
+			network_id: 0,
+		}));
+		assert_noop!(
+			IBP::dispatch_as_root(RuntimeOrigin::signed(member()), call.clone()),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(IBP::dispatch_as_root(RuntimeOrigin::root(), call));
+		System::assert_has_event(crate::Event::RootDispatched { result: Ok(()) }.into());
+		assert!(crate::Services::<Test>::contains_key(0));
+	});
+}
+
+#[test]
+fn applications_are_decided_by_member_vote_or_expire() {
+	new_test_ext().execute_with(|| {
+		add_member(member(), b"Helikon");
+		add_member(other_member(), b"Other");
+		let applicant = AccountId32::new([5; 32]);
+		assert_ok!(IBP::apply_for_membership(
+			RuntimeOrigin::signed(applicant.clone()),
+			bounded(b"Applicant"),
+			bounded(b"https://applicant.example"),
+		));
+		assert_eq!(Balances::reserved_balance(&applicant), 10);
+		assert_noop!(
+			IBP::apply_for_membership(
+				RuntimeOrigin::signed(applicant.clone()),
+				bounded(b"Applicant"),
+				bounded(b""),
+			),
+			Error::<Test>::AlreadyApplied,
+		);
+		assert_noop!(
+			IBP::vote_on_application(RuntimeOrigin::signed(monitor()), applicant.clone(), true),
+			Error::<Test>::MemberNotFound,
+		);
+		assert_ok!(IBP::vote_on_application(
+			RuntimeOrigin::signed(member()),
+			applicant.clone(),
+			true,
+		));
+		assert!(!crate::Members::<Test>::contains_key(&applicant));
+		assert_ok!(IBP::vote_on_application(
+			RuntimeOrigin::signed(other_member()),
+			applicant.clone(),
+			true,
+		));
+		assert!(crate::Members::<Test>::contains_key(&applicant));
+		assert_eq!(Balances::reserved_balance(&applicant), 0);
+
+		let rejected = AccountId32::new([6; 32]);
+		let expired = AccountId32::new([7; 32]);
+		for applicant in [&rejected, &expired] {
+			Balances::make_free_balance_be(applicant, 100);
+			assert_ok!(IBP::apply_for_membership(
+				RuntimeOrigin::signed(applicant.clone()),
+				bounded(b"Applicant"),
+				bounded(b""),
+			));
+		}
+		assert_noop!(
+			IBP::reject_application(RuntimeOrigin::signed(member()), rejected.clone()),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(IBP::reject_application(RuntimeOrigin::root(), rejected.clone()));
+		assert!(!crate::Members::<Test>::contains_key(&rejected));
+		assert_eq!(Balances::free_balance(&rejected), 100);
+
+		IBP::on_initialize(20);
+		assert!(crate::Applications::<Test>::contains_key(&expired));
+		IBP::on_initialize(21);
+		assert!(!crate::Applications::<Test>::contains_key(&expired));
+		assert_eq!(Balances::free_balance(&expired), 100);
+		assert_noop!(
+			IBP::approve_application(RuntimeOrigin::root(), expired),
+			Error::<Test>::ApplicationNotFound,
+		);
+	});
+}
+
+#[test]
+fn members_are_only_admitted_by_approval_up_to_the_maximum() {
+	new_test_ext().execute_with(|| {
+		let applicant = AccountId32::new([5; 32]);
+		assert_ok!(IBP::apply_for_membership(
+			RuntimeOrigin::signed(applicant.clone()),
+			bounded(b"Applicant"),
+			bounded(b""),
+		));
+		// Applying gives no membership, and so no council seat.
+		assert!(!crate::Members::<Test>::contains_key(&applicant));
+		assert_noop!(
+			IBP::approve_application(RuntimeOrigin::signed(applicant.clone()), applicant.clone()),
+			DispatchError::BadOrigin,
+		);
+		add_member(member(), b"Helikon");
+		add_member(other_member(), b"Other");
+		assert_ok!(IBP::approve_application(RuntimeOrigin::root(), applicant));
+
+		let late = AccountId32::new([6; 32]);
+		Balances::make_free_balance_be(&late, 100);
+		assert_ok!(IBP::apply_for_membership(
+			RuntimeOrigin::signed(late.clone()),
+			bounded(b"Late"),
+			bounded(b""),
+		));
+		assert_noop!(
+			IBP::approve_application(RuntimeOrigin::root(), late.clone()),
+			Error::<Test>::TooManyMembers,
+		);
+		assert_ok!(IBP::vote_on_application(RuntimeOrigin::signed(member()), late.clone(), true));
+		assert_noop!(
+			IBP::vote_on_application(RuntimeOrigin::signed(other_member()), late.clone(), true),
+			Error::<Test>::TooManyMembers,
+		);
+		assert!(crate::Applications::<Test>::contains_key(&late));
+		assert_ok!(IBP::reject_application(RuntimeOrigin::root(), late.clone()));
+		assert_eq!(Balances::free_balance(&late), 100);
+	});
+}
+
+#[test]
+fn member_levels_gate_services_and_follow_sla_performance() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		let level = || crate::Members::<Test>::get(member()).unwrap().level;
+		assert_ok!(IBP::set_service_min_level(RuntimeOrigin::root(), 0, 1));
+		let register = || {
+			IBP::register_member_service(
+				RuntimeOrigin::signed(member()),
+				0,
+				bounded(b"helikon-polkadot-rpc-2"),
+				bounded(b"rpc2.helikon.io"),
+				443,
+			)
+		};
+		assert_noop!(register(), Error::<Test>::MemberLevelTooLow);
+		assert_noop!(
+			IBP::set_member_level(RuntimeOrigin::root(), member(), 4),
+			Error::<Test>::InvalidLevel,
+		);
+		assert_ok!(IBP::set_member_level(RuntimeOrigin::root(), member(), 1));
+		System::assert_has_event(
+			crate::Event::MemberPromoted { member: member(), level: 1 }.into(),
+		);
+		assert_ok!(register());
+
+		for (era, up_checks) in [(0, 10), (1, 10), (2, 5), (3, 5)] {
+			crate::EraRollups::<Test>::insert(
+				era,
+				0,
+				crate::EraRollup { checks: 10, up_checks, ..Default::default() },
+			);
+		}
+		IBP::on_initialize(100);
+		assert_eq!(level(), 1);
+		IBP::on_initialize(200);
+		assert_eq!(level(), 2);
+		IBP::on_initialize(300);
+		assert_eq!(level(), 2);
+		IBP::on_initialize(400);
+		assert_eq!(level(), 1);
+		System::assert_has_event(crate::Event::MemberDemoted { member: member(), level: 1 }.into());
+	});
+}
+
+#[test]
+fn membership_transfers_move_deposits_monitors_and_rewards() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		crate::PendingRewards::<Test>::insert(member(), 30);
+		let new_account = AccountId32::new([6; 32]);
+		Balances::make_free_balance_be(&new_account, 10);
+
+		assert_ok!(IBP::transfer_membership(RuntimeOrigin::signed(member()), new_account.clone()));
+		assert_noop!(
+			IBP::accept_membership_transfer(RuntimeOrigin::signed(monitor()), member()),
+			Error::<Test>::TransferNotFound,
+		);
+		assert_ok!(IBP::accept_membership_transfer(
+			RuntimeOrigin::signed(new_account.clone()),
+			member(),
+		));
+		System::assert_has_event(
+			crate::Event::MembershipTransferred { from: member(), to: new_account.clone() }.into(),
+		);
+		assert!(!crate::Members::<Test>::contains_key(member()));
+		assert_eq!(crate::Members::<Test>::get(&new_account).unwrap().id, 0);
+		assert_eq!(Balances::reserved_balance(member()), 0);
+		assert_eq!(Balances::reserved_balance(&new_account), 100);
+		assert_eq!(crate::MemberServiceDeposits::<Test>::get(0).unwrap().depositor, new_account);
+		assert_eq!(crate::MonitorOwners::<Test>::get(monitor()), Some(new_account.clone()));
+		assert_eq!(crate::PendingRewards::<Test>::get(&new_account), 30);
+		assert_eq!(crate::PendingRewards::<Test>::get(member()), 0);
+
+		assert_noop!(
+			IBP::force_transfer_membership(
+				RuntimeOrigin::signed(member()),
+				new_account.clone(),
+				member(),
+			),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(IBP::force_transfer_membership(
+			RuntimeOrigin::root(),
+			new_account.clone(),
+			member(),
+		));
+		assert!(crate::Members::<Test>::contains_key(member()));
+		assert_eq!(Balances::reserved_balance(member()), 100);
+	});
+}
+
+#[test]
+fn membership_transfers_fail_unless_the_whole_deposit_moves() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		let new_account = AccountId32::new([6; 32]);
+		Balances::make_free_balance_be(&new_account, 10);
+		assert_ok!(IBP::transfer_membership(RuntimeOrigin::signed(member()), new_account.clone()));
+		// Part of the member service deposit was unreserved behind the pallet's back.
+		Balances::unreserve(&member(), 40);
+
+		assert_noop!(
+			IBP::accept_membership_transfer(RuntimeOrigin::signed(new_account.clone()), member()),
+			Error::<Test>::ReserveTooLow,
+		);
+		assert_noop!(
+			IBP::force_transfer_membership(RuntimeOrigin::root(), member(), new_account.clone()),
+			Error::<Test>::ReserveTooLow,
+		);
+		assert!(crate::Members::<Test>::contains_key(member()));
+		assert_eq!(Balances::reserved_balance(member()), 60);
+		assert_eq!(Balances::reserved_balance(&new_account), 0);
+	});
+}
+
+#[test]
+fn votes_and_disputes_follow_membership_transfers() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		add_member(other_member(), b"Other");
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			failed_health_check(1_700_000_000_000),
+		));
+		assert_ok!(IBP::dispute_health_check(
+			RuntimeOrigin::signed(member()),
+			0,
+			crate::DisputeTarget::Reports {
+				monitor: monitor(),
+				timestamps: vec![1_700_000_000_000].try_into().unwrap(),
+			},
+		));
+		assert_eq!(crate::DisputesByDisputant::<Test>::get(member()).into_inner(), vec![0]);
+		assert_ok!(IBP::vote_on_dispute(RuntimeOrigin::signed(other_member()), 0, true));
+		let applicant = AccountId32::new([5; 32]);
+		assert_ok!(IBP::apply_for_membership(
+			RuntimeOrigin::signed(applicant.clone()),
+			bounded(b"Applicant"),
+			bounded(b""),
+		));
+		assert_ok!(IBP::vote_on_application(
+			RuntimeOrigin::signed(other_member()),
+			applicant.clone(),
+			false,
+		));
+
+		// The votes of a member stay its own after it moves to another account.
+		let voter = AccountId32::new([7; 32]);
+		Balances::make_free_balance_be(&voter, 10);
+		assert_ok!(IBP::transfer_membership(RuntimeOrigin::signed(other_member()), voter.clone()));
+		assert_ok!(IBP::accept_membership_transfer(
+			RuntimeOrigin::signed(voter.clone()),
+			other_member(),
+		));
+		assert_noop!(
+			IBP::vote_on_dispute(RuntimeOrigin::signed(voter.clone()), 0, true),
+			Error::<Test>::AlreadyVoted,
+		);
+		assert_noop!(
+			IBP::vote_on_application(RuntimeOrigin::signed(voter), applicant, false),
+			Error::<Test>::AlreadyVoted,
+		);
+
+		let disputant = AccountId32::new([6; 32]);
+		Balances::make_free_balance_be(&disputant, 10);
+		assert_ok!(IBP::force_transfer_membership(
+			RuntimeOrigin::root(),
+			member(),
+			disputant.clone(),
+		));
+		assert_eq!(crate::Disputes::<Test>::get(0).unwrap().disputant, disputant);
+		assert_eq!(crate::DisputesByDisputant::<Test>::get(&disputant).into_inner(), vec![0]);
+		assert!(crate::DisputesByDisputant::<Test>::get(member()).is_empty());
+		assert_eq!(crate::MonitorsByOwner::<Test>::get(&disputant).into_inner(), vec![monitor()]);
+		assert_eq!(crate::DepositsByDepositor::<Test>::get(&disputant).into_inner(), vec![0]);
+		assert_eq!(Balances::reserved_balance(&disputant), 150);
+		assert_eq!(Balances::reserved_balance(member()), 0);
+
+		assert_ok!(IBP::resolve_dispute(RuntimeOrigin::root(), 0, true));
+		assert!(crate::DisputesByDisputant::<Test>::get(&disputant).is_empty());
+		assert_eq!(Balances::reserved_balance(&disputant), 100);
+	});
+}
+
+#[test]
+fn migration_to_v12_records_votes_by_member_id_and_indexes_owners() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		add_member(other_member(), b"Other");
+		let applicant = AccountId32::new([5; 32]);
+		let old_application = (
+			bounded::<ConstU32<64>>(b"Applicant"),
+			bounded::<ConstU32<256>>(b""),
+			10u64,
+			20u64,
+			BoundedVec::<AccountId32, ConstU32<32>>::truncate_from(vec![other_member()]),
+			BoundedVec::<AccountId32, ConstU32<32>>::truncate_from(vec![monitor()]),
+		);
+		let key = crate::Applications::<Test>::hashed_key_for(&applicant);
+		frame_support::storage::unhashed::put(&key, &old_application);
+		for account in [member(), other_member()] {
+			crate::DepositsByDepositor::<Test>::remove(&account);
+			crate::MonitorsByOwner::<Test>::remove(&account);
+		}
+		StorageVersion::new(11).put::<IBP>();
+
+		crate::migrations::v12::MigrateToV12::<Test>::on_runtime_upgrade();
+		assert_eq!(StorageVersion::get::<IBP>(), 12);
+		let application = crate::Applications::<Test>::get(&applicant).unwrap();
+		assert_eq!(application.ayes.into_inner(), vec![1]);
+		// The monitor isn't a member, so its vote is dropped.
+		assert!(application.nays.is_empty());
+		assert_eq!(crate::DepositsByDepositor::<Test>::get(member()).into_inner(), vec![0]);
+		assert_eq!(crate::MonitorsByOwner::<Test>::get(member()).into_inner(), vec![monitor()]);
+	});
+}
+
+#[test]
+fn verified_identity_can_be_required_for_approval_and_promotion() {
+	new_test_ext().execute_with(|| {
+		RequireIdentity::set(true);
+		assert_ok!(IBP::apply_for_membership(
+			RuntimeOrigin::signed(member()),
+			bounded(b"Helikon"),
+			bounded(b""),
+		));
+		assert_noop!(
+			IBP::approve_application(RuntimeOrigin::root(), member()),
+			Error::<Test>::IdentityNotVerified,
+		);
+		VerifiedAccounts::set(vec![member()]);
+		assert_ok!(IBP::approve_application(RuntimeOrigin::root(), member()));
+		assert_eq!(
+			IBP::member_details(0).unwrap().identity.unwrap().web,
+			b"https://helikon.io".to_vec(),
+		);
+		assert_ok!(IBP::set_member_level(RuntimeOrigin::root(), member(), 1));
+
+		RequireIdentity::set(false);
+		add_member(other_member(), b"Other");
+		RequireIdentity::set(true);
+		assert_noop!(
+			IBP::set_member_level(RuntimeOrigin::root(), other_member(), 1),
+			Error::<Test>::IdentityNotVerified,
+		);
+		assert_eq!(IBP::member_details(1).unwrap().identity, None);
+	});
+}
+
+#[test]
+fn members_update_contacts_sealed_to_the_current_contact_key() {
+	new_test_ext().execute_with(|| {
+		add_member(member(), b"Helikon");
+		assert_noop!(
+			IBP::set_contact(RuntimeOrigin::signed(member()), 0, bounded(b"sealed")),
+			Error::<Test>::NoContactKey,
+		);
+		assert_noop!(
+			IBP::set_contact_key(RuntimeOrigin::signed(member()), [1; 32]),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(IBP::set_contact_key(RuntimeOrigin::root(), [1; 32]));
+		assert_noop!(
+			IBP::set_contact(RuntimeOrigin::signed(other_member()), 0, bounded(b"sealed")),
+			Error::<Test>::MemberNotFound,
+		);
+		assert_ok!(IBP::set_contact(RuntimeOrigin::signed(member()), 0, bounded(b"sealed")));
+		assert_ok!(IBP::set_contact(RuntimeOrigin::signed(member()), 0, bounded(b"resealed")));
+		let contact = crate::Contacts::<Test>::get(0).unwrap();
+		assert_eq!((contact.key_id, contact.version), (0, 2));
+		assert_eq!(contact.ciphertext.into_inner(), b"resealed".to_vec());
+
+		assert_ok!(IBP::set_contact_key(RuntimeOrigin::root(), [2; 32]));
+		assert_noop!(
+			IBP::set_contact(RuntimeOrigin::signed(member()), 0, bounded(b"sealed")),
+			Error::<Test>::StaleContactKey,
+		);
+		assert_ok!(IBP::set_contact(RuntimeOrigin::signed(member()), 1, bounded(b"sealed")));
+		assert_eq!(crate::Contacts::<Test>::get(0).unwrap().version, 3);
+
+		assert_ok!(IBP::remove_contact(RuntimeOrigin::signed(member())));
+		assert_noop!(
+			IBP::remove_contact(RuntimeOrigin::signed(member())),
+			Error::<Test>::ContactNotFound,
+		);
+	});
+}
+
+/// An up health check of member service 0 at `timestamp` presenting a certificate.
+fn certified_health_check(timestamp: u64, fingerprint: [u8; 32], expires_at: u64) -> HealthCheck {
+	HealthCheck {
+		timestamp,
+		certificate: Some(CertificateReport {
+			fingerprint,
+			issuer: bounded(b"CN=R3, O=Let's Encrypt, C=US"),
+			expires_at,
+		}),
+		..up_health_check(0, 100, 98)
+	}
+}
+
+fn security_incidents() -> usize {
+	System::events()
+		.into_iter()
+		.filter(|record| {
+			matches!(record.event, RuntimeEvent::IBP(crate::Event::SecurityIncident { .. }))
+		})
+		.count()
+}
+
+fn expiry_warnings() -> usize {
+	System::events()
+		.into_iter()
+		.filter(|record| {
+			matches!(record.event, RuntimeEvent::IBP(crate::Event::CertificateExpiringSoon { .. }))
+		})
+		.count()
+}
+
+#[test]
+fn certificate_mismatch_fails_checks_and_is_reported_once_per_fingerprint() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		assert_ok!(IBP::set_expected_certificate(
+			RuntimeOrigin::signed(member()),
+			0,
+			Some(ExpectedCertificate { fingerprint: Some([1; 32]), issuer: None }),
+		));
+		let now = 1_700_000_000_000;
+		let expires_at = now + 90 * MILLISECS_PER_DAY;
+		for (block, fingerprint, incidents) in [(1, [2; 32], 1), (11, [2; 32], 1), (21, [3; 32], 2)]
+		{
+			System::set_block_number(block);
+			assert_ok!(IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				certified_health_check(now, fingerprint, expires_at),
+			));
+			let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+			assert_eq!(
+				history.last().unwrap().result,
+				HealthCheckResult::Failed(FailureCode::CertificateMismatch)
+			);
+			assert_eq!(security_incidents(), incidents);
+		}
+		System::assert_has_event(
+			crate::Event::SecurityIncident {
+				member_service_id: 0,
+				monitor: monitor(),
+				kind: SecurityIncidentKind::CertificateMismatch { fingerprint: [3; 32] },
+			}
+			.into(),
+		);
+		let status = IBP::certificate_status(0).unwrap();
+		assert!(status.mismatch);
+		assert_eq!(status.report.fingerprint, [3; 32]);
+		assert_eq!(status.reported_at, 21);
+
+		System::set_block_number(31);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			certified_health_check(now, [1; 32], expires_at),
+		));
+		let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+		assert_eq!(history.last().unwrap().result, HealthCheckResult::Ok);
+		assert!(!IBP::certificate_status(0).unwrap().mismatch);
+	});
+}
+
+#[test]
+fn expiring_certificates_are_reported_once_and_expired_ones_fail_checks() {
+	new_test_ext().execute_with(|| {
+		register_member_service(None);
+		assert_eq!(IBP::certificate_status(0), None);
+		let now = 1_700_000_000_000;
+		let expires_at = now + 10 * MILLISECS_PER_DAY;
+		for (block, timestamp) in [(1, now), (11, now + MILLISECS_PER_DAY)] {
+			System::set_block_number(block);
+			assert_ok!(IBP::submit_health_check(
+				RuntimeOrigin::signed(monitor()),
+				certified_health_check(timestamp, [1; 32], expires_at),
+			));
+			let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+			assert_eq!(history.last().unwrap().result, HealthCheckResult::Ok);
+			assert_eq!(expiry_warnings(), 1);
+		}
+		System::assert_has_event(
+			crate::Event::CertificateExpiringSoon { member_service_id: 0, expires_at }.into(),
+		);
+		let status = IBP::certificate_status(0).unwrap();
+		assert!(status.expiry_warned);
+		assert!(!status.mismatch);
+		assert_eq!(status.report.expires_at, expires_at);
+
+		System::set_block_number(21);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			certified_health_check(expires_at, [1; 32], expires_at),
+		));
+		let history = HealthChecks::<Test>::get(0, monitor()).unwrap();
+		assert_eq!(
+			history.last().unwrap().result,
+			HealthCheckResult::Failed(FailureCode::CertificateExpired)
+		);
+		assert_eq!(expiry_warnings(), 1);
+
+		// A renewed certificate is warned about again once it nears its own expiry.
+		System::set_block_number(31);
+		assert_ok!(IBP::submit_health_check(
+			RuntimeOrigin::signed(monitor()),
+			certified_health_check(expires_at, [2; 32], expires_at + 90 * MILLISECS_PER_DAY),
+		));
+		let status = IBP::certificate_status(0).unwrap();
+		assert!(!status.expiry_warned);
+		assert_eq!(expiry_warnings(), 1);
+	});
+}
@@ -6,6 +6,7 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use codec::{Decode, Encode, MaxEncodedLen};
 use pallet_grandpa::AuthorityId as GrandpaId;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -15,8 +16,8 @@ use sp_runtime::{
 	traits::{
 		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, One, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
+	ApplyExtrinsicResult, BoundedVec, MultiSignature, RuntimeDebug,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -27,7 +28,8 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		ChangeMembers, ConstBool, ConstU128, ConstU32, ConstU64, ConstU8, EitherOfDiverse,
+		EqualPrivilegeOnly, InstanceFilter, KeyOwnerProofSystem, Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{
@@ -35,9 +37,10 @@ pub use frame_support::{
 		},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
+use frame_system::{EnsureNever, EnsureRoot};
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter, Multiplier};
@@ -87,6 +90,7 @@ pub mod opaque {
 		pub struct SessionKeys {
 			pub aura: Aura,
 			pub grandpa: Grandpa,
+			pub ibp: IBP,
 		}
 	}
 }
@@ -103,10 +107,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 128,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 5,
 	state_version: 1,
 };
 
@@ -267,8 +271,140 @@ impl pallet_sudo::Config for Runtime {
 	type RuntimeCall = RuntimeCall;
 }
 
+parameter_types! {
+	pub const IbpCouncilMotionDuration: BlockNumber = 3 * DAYS;
+	pub const IbpCouncilMaxProposals: u32 = 100;
+	pub const IbpCouncilMaxMembers: u32 = 100;
+	pub MaxProposalWeight: Weight = Perbill::from_percent(50) * BlockWeights::get().max_block;
+}
+
+/// Collective of the IBP members that governs pallet-ibp.
+type IbpCollective = pallet_collective::Instance1;
+
+impl pallet_collective::Config<IbpCollective> for Runtime {
+	type RuntimeOrigin = RuntimeOrigin;
+	type Proposal = RuntimeCall;
+	type RuntimeEvent = RuntimeEvent;
+	type MotionDuration = IbpCouncilMotionDuration;
+	type MaxProposals = IbpCouncilMaxProposals;
+	type MaxMembers = IbpCouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+	type SetMembersOrigin = EnsureRoot<AccountId>;
+	type MaxProposalWeight = MaxProposalWeight;
+}
+
+/// Root, or a majority of the IBP council.
+type IbpCouncilMajority = EitherOfDiverse<
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionMoreThan<AccountId, IbpCollective, 1, 2>,
+>;
+
+/// Root, or two thirds of the IBP council.
+type IbpCouncilSupermajority = EitherOfDiverse<
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionAtLeast<AccountId, IbpCollective, 2, 3>,
+>;
+
+type IbpMembershipInstance = pallet_membership::Instance1;
+
+/// Council membership only changes with the members of pallet-ibp, through
+/// `SyncIbpCouncilMembership`, so no origin may change it.
+impl pallet_membership::Config<IbpMembershipInstance> for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type AddOrigin = EnsureNever<()>;
+	type RemoveOrigin = EnsureNever<()>;
+	type SwapOrigin = EnsureNever<()>;
+	type ResetOrigin = EnsureNever<()>;
+	type PrimeOrigin = IbpCouncilMajority;
+	type MembershipInitialized = IbpCouncil;
+	type MembershipChanged = IbpCouncil;
+	type MaxMembers = IbpCouncilMaxMembers;
+	type WeightInfo = pallet_membership::weights::SubstrateWeight<Runtime>;
+}
+
+/// Resets the council membership to the members registered in pallet-ibp, which admits at most
+/// `IbpCouncilMaxMembers` of them through approved applications. Writes the membership like
+/// `reset_members` does, as no origin may call it, keeping the prime if it's still a member.
+pub struct SyncIbpCouncilMembership;
+
+impl ChangeMembers<AccountId> for SyncIbpCouncilMembership {
+	fn change_members_sorted(_incoming: &[AccountId], _outgoing: &[AccountId], new: &[AccountId]) {
+		let members = match BoundedVec::<AccountId, IbpCouncilMaxMembers>::try_from(new.to_vec()) {
+			Ok(members) => members,
+			Err(_) => {
+				frame_support::defensive!("More IBP members than the council can hold");
+				return
+			},
+		};
+		let prime = pallet_membership::Prime::<Runtime, IbpMembershipInstance>::get()
+			.filter(|prime| members.contains(prime));
+		pallet_membership::Members::<Runtime, IbpMembershipInstance>::mutate(|current| {
+			IbpCouncil::set_members_sorted(&members, current);
+			*current = members;
+		});
+		pallet_membership::Prime::<Runtime, IbpMembershipInstance>::set(prime.clone());
+		IbpCouncil::set_prime(prime);
+	}
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+	/// A hundred health check rewards.
+	pub const PreimageBaseDeposit: Balance = 10_000_000_000_000;
+	pub const PreimageByteDeposit: Balance = 10_000_000_000;
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeOrigin = RuntimeOrigin;
+	type PalletsOrigin = OriginCaller;
+	type RuntimeCall = RuntimeCall;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = IbpCouncilMajority;
+	type MaxScheduledPerBlock = ConstU32<50>;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type Preimages = Preimage;
+}
+
+impl pallet_preimage::Config for Runtime {
+	type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type ManagerOrigin = IbpCouncilMajority;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+}
+
 parameter_types! {
 	pub HealthCheckReward: u64 = 100_000_000_000;
+	pub const HealthCheckInterval: BlockNumber = 10 * MINUTES;
+	pub const IbpEraLength: BlockNumber = DAYS;
+	pub const IbpUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+	/// A hundred health check rewards.
+	pub const IbpDisputeBond: Balance = 10_000_000_000_000;
+	/// A thousand health check rewards.
+	pub const IbpMemberServiceDeposit: Balance = 100_000_000_000_000;
+	pub const IbpPalletId: PalletId = PalletId(*b"ibp/pot_");
+	pub const IbpSloUptime: Perbill = Perbill::from_percent(99);
+	pub const IbpBreachSlash: Perbill = Perbill::from_percent(10);
+	/// Ten thousand health check rewards.
+	pub const IbpMemberEraBudget: Balance = 1_000_000_000_000_000;
+	pub const IbpApplicationDeposit: Balance = 100_000_000_000_000;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = UncheckedExtrinsic;
 }
 
 /// Configure the pallet-ibp in pallets/ibp.
@@ -276,10 +412,225 @@ impl pallet_ibp::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = pallet_ibp::weights::SubstrateWeight<Runtime>;
 	type HealthCheckReward = HealthCheckReward;
+	type HealthCheckInterval = HealthCheckInterval;
+	type AuthorityId = pallet_ibp::crypto::MonitorAuthId;
+	type UnsignedPriority = IbpUnsignedPriority;
+	type ProbeTimeout = ConstU64<5_000>;
+	type MaxProbesPerBlock = ConstU32<8>;
+	type MaxProbeTimePerBlock = ConstU64<{ MILLISECS_PER_BLOCK }>;
+	type EraLength = IbpEraLength;
+	type EraHistoryDepth = ConstU32<84>;
+	type MaxBlockLag = ConstU32<10>;
+	type CertificateExpiryWarningDays = ConstU32<14>;
+	type RequiredAttestations = ConstU32<3>;
+	type MaxMaintenancesPerEra = ConstU32<2>;
+	type MaxMaintenanceDuration = ConstU32<{ 4 * HOURS }>;
+	type MaintenanceNotice = ConstU32<{ 2 * HOURS }>;
+	type IncidentThreshold = ConstU32<3>;
+	type MaxIncidentHistory = ConstU32<1024>;
+	type DisputeBond = IbpDisputeBond;
+	type DisputeVotesRequired = ConstU32<3>;
+	type DisputeTimeout = ConstU32<{ 14 * DAYS }>;
+	type MemberServiceDeposit = IbpMemberServiceDeposit;
+	type MaxMemberServices = ConstU32<512>;
+	type PalletId = IbpPalletId;
+	type SloUptime = IbpSloUptime;
+	type SlashAfterBreaches = ConstU32<3>;
+	type SuspendAfterBreaches = ConstU32<6>;
+	type BreachSlash = IbpBreachSlash;
+	type SlashDeferDuration = ConstU32<7>;
+	type MemberEraBudget = IbpMemberEraBudget;
+	type TargetResponseTime = ConstU32<500>;
+	type Currency = Balances;
+	type AdminOrigin = IbpCouncilMajority;
+	type RegistrarOrigin = IbpCouncilMajority;
+	type SlashOrigin = IbpCouncilSupermajority;
+	type RootDispatchOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, IbpCollective, 2, 3>;
+	type RuntimeCall = RuntimeCall;
+	type MembersChanged = SyncIbpCouncilMembership;
+	type MaxMembers = IbpCouncilMaxMembers;
+	type ApplicationDeposit = IbpApplicationDeposit;
+	type ApplicationTimeout = ConstU32<{ 14 * DAYS }>;
+	type ApplicationVotesRequired = ConstU32<3>;
+	type MaxMemberLevel = ConstU8<7>;
+	type PromotionEras = ConstU32<90>;
+	type DemotionEras = ConstU32<7>;
+	type IdentityVerifier = IbpIdentityVerifier;
+	type RequireIdentityForApproval = ConstBool<true>;
+	type RequireIdentityForPromotion = ConstBool<true>;
+}
+
+/// Calls a proxy may make on behalf of the proxied account.
+#[derive(
+	Copy,
+	Clone,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
+pub enum ProxyType {
+	/// Any call.
+	Any,
+	/// Any call that doesn't move balances, rewards or the membership, or use the sudo key.
+	NonTransfer,
+	/// Management of a member's member services and their maintenance windows.
+	IbpOperator,
+	/// Domain attestations of a monitor. Monitors delegate health checks to an `ibpm` key bound
+	/// with `set_monitor_key` instead, as proxied calls skip the `CheckHealthCheck` extension.
+	IbpMonitor,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<RuntimeCall> for ProxyType {
+	fn filter(&self, call: &RuntimeCall) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::NonTransfer => !matches!(
+				call,
+				RuntimeCall::Balances(..) |
+					RuntimeCall::Sudo(..) |
+					RuntimeCall::IBP(
+						pallet_ibp::Call::transfer_membership { .. } |
+							pallet_ibp::Call::accept_membership_transfer { .. } |
+							pallet_ibp::Call::claim_rewards { .. }
+					)
+			),
+			ProxyType::IbpOperator => matches!(
+				call,
+				RuntimeCall::IBP(
+					pallet_ibp::Call::register_member_service { .. } |
+						pallet_ibp::Call::deregister_member_service { .. } |
+						pallet_ibp::Call::set_bootnode { .. } |
+						pallet_ibp::Call::set_expected_certificate { .. } |
+						pallet_ibp::Call::schedule_maintenance { .. } |
+						pallet_ibp::Call::set_post_mortem { .. }
+				)
+			),
+			ProxyType::IbpMonitor =>
+				matches!(call, RuntimeCall::IBP(pallet_ibp::Call::attest_domain { .. })),
+		}
+	}
+
+	fn is_superset(&self, other: &Self) -> bool {
+		match (self, other) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::NonTransfer, _) => true,
+			_ => false,
+		}
+	}
+}
+
+parameter_types! {
+	/// A hundred health check rewards.
+	pub const ProxyDepositBase: Balance = 10_000_000_000_000;
+	/// One health check reward per proxy.
+	pub const ProxyDepositFactor: Balance = 100_000_000_000;
+	pub const AnnouncementDepositBase: Balance = 10_000_000_000_000;
+	pub const AnnouncementDepositFactor: Balance = 200_000_000_000;
+	/// A hundred health check rewards.
+	pub const MultisigDepositBase: Balance = 10_000_000_000_000;
+	/// One health check reward per signatory.
+	pub const MultisigDepositFactor: Balance = 100_000_000_000;
+}
+
+impl pallet_proxy::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = ConstU32<32>;
+	type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+	type MaxPending = ConstU32<32>;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+impl pallet_multisig::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type RuntimeCall = RuntimeCall;
+	type Currency = Balances;
+	type DepositBase = MultisigDepositBase;
+	type DepositFactor = MultisigDepositFactor;
+	type MaxSignatories = ConstU32<100>;
+	type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	/// A hundred health check rewards.
+	pub const IdentityBasicDeposit: Balance = 10_000_000_000_000;
+	/// Twenty five health check rewards.
+	pub const IdentityFieldDeposit: Balance = 2_500_000_000_000;
+	/// Twenty health check rewards.
+	pub const IdentitySubAccountDeposit: Balance = 2_000_000_000_000;
+	/// Judgement that a registrar must give, or `KnownGood`, for an identity to count as
+	/// verified by pallet-ibp.
+	pub const IbpRequiredJudgement: pallet_identity::Judgement<Balance> =
+		pallet_identity::Judgement::Reasonable;
+}
+
+/// Registrars are added and identities are force-set or killed by the IBP council.
+impl pallet_identity::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
 	type Currency = Balances;
+	type BasicDeposit = IdentityBasicDeposit;
+	type FieldDeposit = IdentityFieldDeposit;
+	type SubAccountDeposit = IdentitySubAccountDeposit;
+	type MaxSubAccounts = ConstU32<100>;
+	type MaxAdditionalFields = ConstU32<100>;
+	type MaxRegistrars = ConstU32<20>;
+	type Slashed = ();
+	type ForceOrigin = IbpCouncilMajority;
+	type RegistrarOrigin = IbpCouncilMajority;
+	type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
+}
+
+/// Provides pallet-ibp with the identities that `Identity` registrars judged at
+/// `IbpRequiredJudgement`.
+pub struct IbpIdentityVerifier;
+
+impl pallet_ibp::VerifyIdentity<AccountId> for IbpIdentityVerifier {
+	fn verified_identity(who: &AccountId) -> Option<pallet_ibp::VerifiedIdentity> {
+		let registration = Identity::identity(who)?;
+		let required = IbpRequiredJudgement::get();
+		let judged = registration.judgements.iter().any(|(_, judgement)| {
+			*judgement == required || *judgement == pallet_identity::Judgement::KnownGood
+		});
+		if !judged {
+			return None
+		}
+		let raw = |data: &pallet_identity::Data| match data {
+			pallet_identity::Data::Raw(bytes) => bytes.to_vec(),
+			_ => Vec::new(),
+		};
+		let info = &registration.info;
+		Some(pallet_ibp::VerifiedIdentity {
+			display: raw(&info.display),
+			web: raw(&info.web),
+			email: raw(&info.email),
+		})
+	}
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
+// rustfmt would drop the `::` before the instances, which `construct_runtime!` requires.
+#[rustfmt::skip]
 construct_runtime!(
 	pub struct Runtime
 	where
@@ -294,6 +645,13 @@ construct_runtime!(
 		Balances: pallet_balances,
 		TransactionPayment: pallet_transaction_payment,
 		Sudo: pallet_sudo,
+		IbpCouncil: pallet_collective::<Instance1>,
+		IbpCouncilMembership: pallet_membership::<Instance1>,
+		Scheduler: pallet_scheduler,
+		Preimage: pallet_preimage,
+		Proxy: pallet_proxy,
+		Multisig: pallet_multisig,
+		Identity: pallet_identity,
 		// Include the custom logic from the pallet-ibp in the runtime.
 		IBP: pallet_ibp,
 	}
@@ -315,6 +673,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_ibp::CheckHealthCheck<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -322,6 +681,22 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Migrations to apply on runtime upgrade.
+pub type Migrations = (
+	pallet_ibp::migrations::v1::MigrateToV1<Runtime>,
+	pallet_ibp::migrations::v2::MigrateToV2<Runtime>,
+	pallet_ibp::migrations::v3::MigrateToV3<Runtime>,
+	pallet_ibp::migrations::v4::MigrateToV4<Runtime>,
+	pallet_ibp::migrations::v5::MigrateToV5<Runtime>,
+	pallet_ibp::migrations::v6::MigrateToV6<Runtime>,
+	pallet_ibp::migrations::v7::MigrateToV7<Runtime>,
+	pallet_ibp::migrations::v8::MigrateToV8<Runtime>,
+	pallet_ibp::migrations::v9::MigrateToV9<Runtime>,
+	pallet_ibp::migrations::v10::MigrateToV10<Runtime>,
+	pallet_ibp::migrations::v11::MigrateToV11<Runtime>,
+	pallet_ibp::migrations::v12::MigrateToV12<Runtime>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -329,6 +704,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -341,6 +717,14 @@ mod benches {
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_collective, IbpCouncil]
+		[pallet_identity, Identity]
+		// No origin may change the IBP council membership, so `pallet_membership` can't be
+		// benchmarked.
+		[pallet_multisig, Multisig]
+		[pallet_preimage, Preimage]
+		[pallet_proxy, Proxy]
+		[pallet_scheduler, Scheduler]
 		[pallet_timestamp, Timestamp]
 		[pallet_ibp, IBP]
 	);
@@ -470,6 +854,25 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(3)]
+	impl pallet_ibp_runtime_api::IbpApi<Block, BlockNumber> for Runtime {
+		fn certificate_status(
+			member_service_id: u32,
+		) -> Option<pallet_ibp_runtime_api::CertificateStatus<BlockNumber>> {
+			IBP::certificate_status(member_service_id)
+		}
+
+		fn maintenance_windows(
+			member_service_id: u32,
+		) -> Vec<pallet_ibp_runtime_api::MaintenanceWindow<BlockNumber>> {
+			IBP::maintenance_windows(member_service_id)
+		}
+
+		fn member(member_id: u32) -> Option<pallet_ibp_runtime_api::MemberDetails> {
+			IBP::member_details(member_id)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -581,10 +984,263 @@ impl_runtime_apis! {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use frame_support::traits::WhitelistedStorageKeys;
+	use frame_support::{assert_noop, assert_ok, sp_io, traits::WhitelistedStorageKeys};
 	use sp_core::hexdisplay::HexDisplay;
+	use sp_runtime::{traits::Hash, DispatchError, DispatchResult, MultiAddress};
 	use std::collections::HashSet;
 
+	fn alice() -> AccountId {
+		AccountId::from([1; 32])
+	}
+
+	fn bob() -> AccountId {
+		AccountId::from([2; 32])
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut storage =
+			frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+		pallet_balances::GenesisConfig::<Runtime> {
+			balances: vec![(alice(), 1 << 60), (bob(), 1 << 60)],
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+		let mut ext = sp_io::TestExternalities::new(storage);
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+
+	fn transfer_call() -> RuntimeCall {
+		RuntimeCall::Balances(pallet_balances::Call::transfer_keep_alive {
+			dest: MultiAddress::Id(bob()),
+			value: 1_000,
+		})
+	}
+
+	fn register_monitor_call() -> RuntimeCall {
+		RuntimeCall::IBP(pallet_ibp::Call::register_monitor {
+			monitor: bob(),
+			name: b"monitor".to_vec().try_into().unwrap(),
+		})
+	}
+
+	fn deregister_member_service_call() -> RuntimeCall {
+		RuntimeCall::IBP(pallet_ibp::Call::deregister_member_service { member_service_id: 0 })
+	}
+
+	fn submit_health_check_call() -> RuntimeCall {
+		RuntimeCall::IBP(pallet_ibp::Call::submit_health_check {
+			health_check: pallet_ibp::HealthCheck {
+				member_service_id: 0,
+				timestamp: 0,
+				result: pallet_ibp::HealthCheckResult::Ok,
+				response_time_ms: 0,
+				best_block: None,
+				finalized_block: None,
+				peer_count: None,
+				is_syncing: None,
+				genesis_hash: None,
+				certificate: None,
+			},
+		})
+	}
+
+	fn attest_domain_call() -> RuntimeCall {
+		RuntimeCall::IBP(pallet_ibp::Call::attest_domain {
+			member_service_id: 0,
+			token: Default::default(),
+		})
+	}
+
+	fn remark_call() -> RuntimeCall {
+		RuntimeCall::System(frame_system::Call::remark { remark: b"ibp".to_vec() })
+	}
+
+	/// Let Bob dispatch calls of `proxy_type` as Alice.
+	fn add_proxy(proxy_type: ProxyType) {
+		assert_ok!(Proxy::add_proxy(
+			RuntimeOrigin::signed(alice()),
+			MultiAddress::Id(bob()),
+			proxy_type,
+			0,
+		));
+	}
+
+	fn call_filtered() -> DispatchResult {
+		Err(frame_system::Error::<Runtime>::CallFiltered.into())
+	}
+
+	/// Dispatch `call` as Alice through Bob's proxy, and return its result.
+	fn proxy(call: RuntimeCall) -> DispatchResult {
+		assert_ok!(Proxy::proxy(
+			RuntimeOrigin::signed(bob()),
+			MultiAddress::Id(alice()),
+			None,
+			Box::new(call),
+		));
+		System::events()
+			.into_iter()
+			.rev()
+			.find_map(|record| match record.event {
+				RuntimeEvent::Proxy(pallet_proxy::Event::ProxyExecuted { result }) => Some(result),
+				_ => None,
+			})
+			.expect("proxied calls emit ProxyExecuted")
+	}
+
+	#[test]
+	fn proxy_types_filter_calls() {
+		assert!(ProxyType::Any.filter(&transfer_call()));
+		assert!(!ProxyType::NonTransfer.filter(&transfer_call()));
+		assert!(ProxyType::NonTransfer.filter(&register_monitor_call()));
+		assert!(ProxyType::IbpOperator.filter(&deregister_member_service_call()));
+		assert!(!ProxyType::IbpOperator.filter(&register_monitor_call()));
+		assert!(!ProxyType::IbpOperator.filter(&transfer_call()));
+		assert!(ProxyType::IbpMonitor.filter(&attest_domain_call()));
+		assert!(!ProxyType::IbpMonitor.filter(&submit_health_check_call()));
+		assert!(!ProxyType::IbpMonitor.filter(&register_monitor_call()));
+		assert!(ProxyType::NonTransfer.is_superset(&ProxyType::IbpOperator));
+		assert!(!ProxyType::IbpOperator.is_superset(&ProxyType::IbpMonitor));
+	}
+
+	#[test]
+	fn operator_proxy_dispatches_only_member_service_calls() {
+		new_test_ext().execute_with(|| {
+			add_proxy(ProxyType::IbpOperator);
+			assert_eq!(proxy(transfer_call()), call_filtered());
+			// Monitors and disputes are left to the member's own account.
+			assert_eq!(proxy(register_monitor_call()), call_filtered());
+			assert_eq!(
+				proxy(RuntimeCall::IBP(pallet_ibp::Call::set_monitor_key {
+					monitor: bob(),
+					key: sp_core::sr25519::Public::from_raw([1; 32]).into(),
+				})),
+				call_filtered(),
+			);
+			assert_eq!(
+				proxy(RuntimeCall::IBP(pallet_ibp::Call::dispute_health_check {
+					member_service_id: 0,
+					target: pallet_ibp::DisputeTarget::Incident(0),
+				})),
+				call_filtered(),
+			);
+			// Passes the filter, and fails in pallet-ibp as Alice isn't a member.
+			assert_eq!(
+				proxy(deregister_member_service_call()),
+				Err(pallet_ibp::Error::<Runtime>::MemberNotFound.into()),
+			);
+		});
+	}
+
+	#[test]
+	fn monitor_proxy_cannot_submit_health_checks() {
+		new_test_ext().execute_with(|| {
+			add_proxy(ProxyType::IbpMonitor);
+			assert_eq!(proxy(submit_health_check_call()), call_filtered());
+			// Passes the filter, and fails in pallet-ibp as Alice isn't a monitor.
+			assert_eq!(
+				proxy(attest_domain_call()),
+				Err(pallet_ibp::Error::<Runtime>::MonitorNotFound.into()),
+			);
+		});
+	}
+
+	#[test]
+	fn non_transfer_proxy_cannot_move_balances() {
+		new_test_ext().execute_with(|| {
+			add_proxy(ProxyType::NonTransfer);
+			assert_eq!(proxy(transfer_call()), call_filtered());
+			assert_eq!(
+				proxy(RuntimeCall::Sudo(pallet_sudo::Call::sudo { call: Box::new(remark_call()) })),
+				call_filtered(),
+			);
+			assert_eq!(
+				proxy(RuntimeCall::IBP(pallet_ibp::Call::accept_membership_transfer {
+					from: bob(),
+				})),
+				call_filtered(),
+			);
+			assert_eq!(
+				proxy(RuntimeCall::IBP(pallet_ibp::Call::claim_rewards {})),
+				call_filtered()
+			);
+			assert_ok!(proxy(remark_call()));
+		});
+	}
+
+	#[test]
+	fn multisig_dispatches_from_its_account() {
+		new_test_ext().execute_with(|| {
+			let multisig = Multisig::multi_account_id(&[alice(), bob()], 1);
+			Balances::make_free_balance_be(&multisig, 1 << 40);
+			assert_ok!(Multisig::as_multi_threshold_1(
+				RuntimeOrigin::signed(alice()),
+				vec![bob()],
+				Box::new(transfer_call()),
+			));
+			assert_eq!(Balances::free_balance(&multisig), (1 << 40) - 1_000);
+			assert_eq!(Balances::free_balance(&bob()), (1 << 60) + 1_000);
+		});
+	}
+
+	/// Give `who` an identity that Bob, as a registrar, judged at `IbpRequiredJudgement`.
+	fn verify_identity(who: AccountId) {
+		assert_ok!(Identity::add_registrar(RuntimeOrigin::root(), MultiAddress::Id(bob())));
+		let raw = |data: &[u8]| pallet_identity::Data::Raw(data.to_vec().try_into().unwrap());
+		let info = pallet_identity::IdentityInfo {
+			additional: Default::default(),
+			display: raw(b"Helikon"),
+			legal: pallet_identity::Data::None,
+			web: raw(b"https://helikon.io"),
+			riot: pallet_identity::Data::None,
+			email: pallet_identity::Data::None,
+			pgp_fingerprint: None,
+			image: pallet_identity::Data::None,
+			twitter: pallet_identity::Data::None,
+		};
+		assert_ok!(Identity::set_identity(
+			RuntimeOrigin::signed(who.clone()),
+			Box::new(info.clone())
+		));
+		assert_ok!(Identity::provide_judgement(
+			RuntimeOrigin::signed(bob()),
+			0,
+			MultiAddress::Id(who),
+			IbpRequiredJudgement::get(),
+			BlakeTwo256::hash_of(&info),
+		));
+	}
+
+	#[test]
+	fn council_follows_the_ibp_members() {
+		new_test_ext().execute_with(|| {
+			verify_identity(alice());
+			assert_ok!(IBP::apply_for_membership(
+				RuntimeOrigin::signed(alice()),
+				b"Helikon".to_vec().try_into().unwrap(),
+				Default::default(),
+			));
+			assert_ok!(IBP::approve_application(RuntimeOrigin::root(), alice()));
+			assert_eq!(IbpCouncilMembership::members().to_vec(), vec![alice()]);
+			assert_eq!(IbpCouncil::members(), vec![alice()]);
+
+			assert_ok!(IBP::transfer_membership(RuntimeOrigin::signed(alice()), bob()));
+			assert_ok!(IBP::accept_membership_transfer(RuntimeOrigin::signed(bob()), alice()));
+			assert_eq!(IbpCouncilMembership::members().to_vec(), vec![bob()]);
+			assert_eq!(IbpCouncil::members(), vec![bob()]);
+
+			// Only pallet-ibp changes the council.
+			assert_noop!(
+				IbpCouncilMembership::add_member(RuntimeOrigin::root(), MultiAddress::Id(alice())),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				IbpCouncilMembership::reset_members(RuntimeOrigin::root(), vec![alice()]),
+				DispatchError::BadOrigin,
+			);
+		});
+	}
+
 	#[test]
 	fn check_whitelist() {
 		let whitelist: HashSet<String> = AllPalletsWithSystem::whitelisted_storage_keys()